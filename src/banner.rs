@@ -0,0 +1,87 @@
+use std::error::Error;
+
+use image::Rgba;
+use imageproc::drawing::draw_text_mut;
+use rusttype::{Font, Scale};
+use serde::{Deserialize, Serialize};
+
+use crate::layout::Area;
+use crate::panel::{BACKGROUND, TEXT, fit_scale};
+use crate::splitscreen::{Frame, FramePixel, RenderInfo, text_bounds};
+
+/// Banner across the top of the video with the number and name of the
+/// current segment.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Banner {
+    /// Height of the banner as a fraction of the video
+    pub size: f64,
+    /// Duration in seconds of fading the text in and out when the segment
+    /// changes, 0 to switch immediately
+    pub fade: f64
+}
+
+impl Banner {
+    /// Splits `area` into the banner and the rest below it.
+    pub fn split(&self, area: Area) -> Result<(Area, Area), Box<dyn Error>> {
+        if !(self.size > 0.0 && self.size < 1.0) {
+            Err(format!("invalid banner size: {}", self.size))?;
+        }
+        if self.fade < 0.0 {
+            Err(format!("invalid banner fade: {}", self.fade))?;
+        }
+
+        let height = (area.height as f64 * self.size) as u32;
+        Ok((
+            Area { height, ..area },
+            Area { y: area.y + height, height: area.height - height, ..area }
+        ))
+    }
+
+    /// Draws the text of the segment at `frame_idx`, e.g. "3/12 — Forest".
+    /// The first segment is shown during the pre-roll.
+    pub fn draw<P: FramePixel>(&self, frame: &mut Frame<P>, font: &Font, info: &RenderInfo, area: Area, fps: u32, frame_idx: u32) {
+        let splits = &info.tiles[0].splits;
+        let (i, start) = match splits.iter().rposition(|(start, _)| *start <= frame_idx) {
+            Some(i) => (i, splits[i].0),
+            None => (0, 0)
+        };
+
+        // fade in after the segment started and out before the next one
+        let mut opacity = 1.0;
+        let fade = self.fade * fps as f64;
+        if fade > 0.0 {
+            opacity = ((frame_idx - start) as f64 / fade).min(opacity);
+            if let Some((next, _)) = splits.get(i + 1) {
+                opacity = ((next - frame_idx) as f64 / fade).min(opacity);
+            }
+        }
+        if opacity <= 0.0 {
+            return;
+        }
+        let color = Rgba([0, 1, 2, 3].map(|c| {
+            (BACKGROUND.0[c] as f64 + (TEXT.0[c] as f64 - BACKGROUND.0[c] as f64) * opacity) as u8
+        }));
+
+        let text = match &info.segments[i] {
+            Some(name) => format!("{}/{} — {}", i + 1, splits.len(), name),
+            None => format!("{}/{}", i + 1, splits.len())
+        };
+        let pad = area.height / 8;
+        let scale = fit_scale(font, Scale::uniform(area.height as f32 * 0.6), &text,
+            area.width.saturating_sub(2 * pad));
+        let (x_min, y_min, width, height) = text_bounds(font, scale, &text);
+        let x = (area.x + area.width / 2) as i32 - width as i32 / 2 - x_min;
+        let y = (area.y + area.height / 2) as i32 - height as i32 / 2 - y_min;
+        draw_text_mut(frame, P::from_rgba(color), x.max(0) as u32, y.max(0) as u32, scale, font, &text);
+    }
+}
+
+impl Default for Banner {
+    fn default() -> Banner {
+        Banner {
+            size: 0.08,
+            fade: 0.0
+        }
+    }
+}
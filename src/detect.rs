@@ -0,0 +1,331 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use image::imageops::{self, FilterType};
+
+use crate::splitscreen::{Split, SplitTime, find_exec, format_time, probe};
+
+/// Size of the grayscale thumbnails that frames are compared at
+const THUMB_SIZE: u32 = 64;
+
+/// Fraction of pixels that must be dark for a frame to count as dark
+const DARK_COVERAGE: f64 = 0.98;
+
+#[derive(Debug, Clone)]
+pub struct Reference {
+    pub name: Option<String>,
+    thumb: Vec<u8>
+}
+
+impl Reference {
+    pub fn load(path: &Path) -> Result<Reference, Box<dyn Error>> {
+        let img = image::open(path)
+            .map_err(|e| format!("cannot open {}: {}", path.display(), e))?
+            .to_luma8();
+        let thumb = imageops::resize(&img, THUMB_SIZE, THUMB_SIZE, FilterType::Triangle);
+        let name = path.file_stem()
+            .map(|s| s.to_string_lossy().into_owned());
+        Ok(Reference { name, thumb: thumb.into_raw() })
+    }
+
+    /// Similarity between 0 and 1 of this reference and a thumbnail.
+    fn similarity(&self, thumb: &[u8]) -> f64 {
+        let diff: u64 = self.thumb.iter().zip(thumb.iter())
+            .map(|(a, b)| (*a as i32 - *b as i32).unsigned_abs() as u64)
+            .sum();
+        1.0 - diff as f64 / (255.0 * self.thumb.len() as f64)
+    }
+}
+
+/// Finds a split for each reference image, in order.
+///
+/// Each reference is matched to the first run of frames after the previous
+/// split whose similarity is at least `threshold`, taking the most similar
+/// frame of that run. If there is no such run, the most similar frame after
+/// the previous split is used.
+pub fn detect_references(video_path: &Path, refs: &[Reference], threshold: f64) -> Result<Vec<Split>, Box<dyn Error>> {
+    let video = probe(video_path)?;
+
+    let frame_time = video.frame_rate.1 as f64 / video.frame_rate.0 as f64;
+    let mut matcher = Matcher::new(refs, threshold, frame_time);
+    let mut start = 0;
+    while matcher.current < refs.len() {
+        // without a run, the frames after the best match have to be decoded
+        // again for the next reference
+        let mut frame_idx = 0;
+        decode_thumbs(video_path, |thumb| {
+            if frame_idx >= start {
+                matcher.feed(frame_idx, thumb.to_vec());
+            }
+            frame_idx += 1;
+        })?;
+        start = matcher.finish()?;
+    }
+
+    let res = matcher.splits.iter().zip(refs.iter())
+        .map(|(frame, reference)| Split {
+            time: SplitTime::Frame(*frame as u64),
+            name: reference.name.clone(),
+            line: None
+        })
+        .collect();
+    Ok(res)
+}
+
+/// Matches the references one at a time against a stream of thumbnails.
+struct Matcher<'a> {
+    refs: &'a [Reference],
+    threshold: f64,
+    /// Duration of a frame in seconds
+    frame_time: f64,
+    /// Index of the reference that is matched next
+    current: usize,
+    /// Whether the last frame was part of a run above the threshold
+    in_run: bool,
+    /// Most similar frame and its similarity in the current run or, without
+    /// a run, since the previous split
+    best: Option<(usize, f64)>,
+    /// Frames of the current run after the best frame, which follow the
+    /// split and have to be matched against the next reference
+    tail: Vec<(usize, Vec<u8>)>,
+    /// Frame of each matched reference
+    splits: Vec<usize>
+}
+
+impl<'a> Matcher<'a> {
+    fn new(refs: &'a [Reference], threshold: f64, frame_time: f64) -> Matcher<'a> {
+        Matcher {
+            refs,
+            threshold,
+            frame_time,
+            current: 0,
+            in_run: false,
+            best: None,
+            tail: Vec::new(),
+            splits: Vec::new()
+        }
+    }
+
+    fn feed(&mut self, frame_idx: usize, thumb: Vec<u8>) {
+        let mut queue = VecDeque::new();
+        queue.push_back((frame_idx, thumb));
+
+        while let Some((frame_idx, thumb)) = queue.pop_front() {
+            let reference = match self.refs.get(self.current) {
+                Some(reference) => reference,
+                None => return
+            };
+            let similarity = reference.similarity(&thumb);
+            let better = self.best.is_none_or(|(_, best)| similarity > best);
+
+            if similarity >= self.threshold {
+                if !self.in_run {
+                    // a run is preferred over the best frame outside of runs
+                    self.in_run = true;
+                    self.best = None;
+                }
+                if better {
+                    self.best = Some((frame_idx, similarity));
+                    self.tail.clear();
+                } else {
+                    self.tail.push((frame_idx, thumb));
+                }
+            } else if self.in_run {
+                self.tail.push((frame_idx, thumb));
+                let tail = self.next_reference();
+                for frame in tail.into_iter().rev() {
+                    queue.push_front(frame);
+                }
+            } else if better {
+                self.best = Some((frame_idx, similarity));
+            }
+        }
+    }
+
+    /// Handles the end of the video and returns the frame to continue
+    /// matching at if the video has to be decoded again.
+    fn finish(&mut self) -> Result<usize, Box<dyn Error>> {
+        while self.in_run {
+            let tail = self.next_reference();
+            for (frame_idx, thumb) in tail {
+                self.feed(frame_idx, thumb);
+            }
+        }
+
+        let reference = match self.refs.get(self.current) {
+            Some(reference) => reference,
+            None => return Ok(0)
+        };
+        let name = reference.name.as_deref().unwrap_or("<unnamed>");
+        let (frame_idx, similarity) = self.best
+            .ok_or_else(|| format!("no frames left to match `{}`", name))?;
+        eprintln!("warning: no frame reaches the threshold for `{}`, using the best match with similarity {:.3}",
+            name, similarity);
+        self.next_reference();
+        Ok(frame_idx + 1)
+    }
+
+    /// Records the best frame as the split of the current reference and
+    /// returns the frames after it that were already decoded.
+    fn next_reference(&mut self) -> Vec<(usize, Vec<u8>)> {
+        if let Some((frame_idx, similarity)) = self.best.take() {
+            let name = self.refs[self.current].name.as_deref().unwrap_or("<unnamed>");
+            eprintln!("[splitscreen] found `{}` at {} (similarity {:.3})",
+                name, format_time(frame_idx as f64 * self.frame_time), similarity);
+            self.splits.push(frame_idx);
+        }
+        self.current += 1;
+        self.in_run = false;
+        std::mem::take(&mut self.tail)
+    }
+}
+
+/// Finds a split at the start of every run of dark frames that lasts at
+/// least `min_duration` seconds. A frame is dark if nearly all of its pixels
+/// have a luma of at most `luma`.
+pub fn detect_black(video_path: &Path, luma: u8, min_duration: f64) -> Result<Vec<Split>, Box<dyn Error>> {
+    let video = probe(video_path)?;
+    let frame_time = video.frame_rate.1 as f64 / video.frame_rate.0 as f64;
+    let min_frames = ((min_duration / frame_time).ceil() as usize).max(1);
+    let min_dark = (DARK_COVERAGE * (THUMB_SIZE * THUMB_SIZE) as f64).ceil() as usize;
+
+    let mut res = Vec::new();
+    let mut frame_idx = 0;
+    let mut run_start = None;
+    let mut push_run = |run_start: usize, run_end: usize| {
+        if run_end - run_start >= min_frames {
+            let time = run_start as f64 * frame_time;
+            eprintln!("[splitscreen] found dark frames from {} to {}",
+                format_time(time), format_time(run_end as f64 * frame_time));
+            res.push(Split {
+                time: SplitTime::Seconds(time),
+                name: None,
+                line: None
+            });
+        }
+    };
+
+    decode_thumbs(video_path, |thumb| {
+        let dark = thumb.iter().filter(|v| **v <= luma).count() >= min_dark;
+        match (dark, run_start) {
+            (true, None) =>
+                run_start = Some(frame_idx),
+            (false, Some(start)) => {
+                push_run(start, frame_idx);
+                run_start = None;
+            },
+            _ => {}
+        }
+        frame_idx += 1;
+    })?;
+    if let Some(start) = run_start {
+        push_run(start, frame_idx);
+    }
+
+    Ok(res)
+}
+
+/// Writes splits in the split file format.
+pub fn write_splits<W: Write>(mut output: W, splits: &[Split]) -> Result<(), Box<dyn Error>> {
+    for split in splits {
+        if let Some(name) = &split.name {
+            writeln!(output, "split {} {}", split.time, name)?;
+        } else {
+            writeln!(output, "split {}", split.time)?;
+        }
+    }
+    Ok(())
+}
+
+/// Decodes every frame of a video as a grayscale thumbnail.
+fn decode_thumbs<F>(video_path: &Path, mut f: F) -> Result<(), Box<dyn Error>>
+    where F: FnMut(&[u8])
+{
+    let ffmpeg = find_exec("ffmpeg").ok_or("ffmpeg not found")?;
+
+    let mut ffmpeg = Command::new(&ffmpeg)
+        .arg("-hwaccel").arg("auto")
+        .arg("-i").arg(video_path)
+        .arg("-an")
+        .arg("-vf").arg(format!("scale={}:{}", THUMB_SIZE, THUMB_SIZE))
+        .arg("-pix_fmt").arg("gray")
+        .arg("-f").arg("rawvideo")
+        .arg("-")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    let mut stdout = ffmpeg.stdout.take().unwrap();
+    let mut buf = vec![0u8; (THUMB_SIZE * THUMB_SIZE) as usize];
+    while stdout.read_exact(&mut buf[..]).is_ok() {
+        f(&buf);
+    }
+
+    let exit = ffmpeg.wait()?;
+    if !exit.success() {
+        Err(format!("ffmpeg could not decode {}", video_path.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reference with a single pixel thumbnail of `luma`
+    fn reference(luma: u8) -> Reference {
+        Reference { name: None, thumb: vec![luma] }
+    }
+
+    fn feed(matcher: &mut Matcher, frames: &[u8]) {
+        for (frame_idx, luma) in frames.iter().enumerate() {
+            matcher.feed(frame_idx, vec![*luma]);
+        }
+    }
+
+    #[test]
+    fn best_frame_of_run() {
+        let refs = [reference(100)];
+        let mut matcher = Matcher::new(&refs, 0.95, 1.0);
+        // frames 1 to 3 are a run above the threshold, frame 5 is better but
+        // comes after it
+        feed(&mut matcher, &[0, 90, 99, 95, 0, 100]);
+        assert_eq!(matcher.splits, vec![2]);
+        assert_eq!(matcher.finish().unwrap(), 0);
+    }
+
+    #[test]
+    fn best_frame_below_threshold() {
+        let refs = [reference(100), reference(0)];
+        let mut matcher = Matcher::new(&refs, 0.99, 1.0);
+        feed(&mut matcher, &[0, 50, 80, 60]);
+        assert!(matcher.splits.is_empty());
+        // matching continues after the best frame
+        assert_eq!(matcher.finish().unwrap(), 3);
+        assert_eq!(matcher.splits, vec![2]);
+        assert_eq!(matcher.current, 1);
+    }
+
+    #[test]
+    fn no_frames_left() {
+        let refs = [reference(100)];
+        let mut matcher = Matcher::new(&refs, 0.99, 1.0);
+        assert!(matcher.finish().is_err());
+    }
+
+    #[test]
+    fn frames_after_match_go_to_next_reference() {
+        let refs = [reference(100), reference(104), reference(0)];
+        let mut matcher = Matcher::new(&refs, 0.98, 1.0);
+        // frame 1 is part of the run of the first reference, but after its
+        // best frame, so it is the best match of the second reference
+        feed(&mut matcher, &[100, 104, 0, 200, 0]);
+        assert_eq!(matcher.splits, vec![0, 1, 2]);
+        assert_eq!(matcher.finish().unwrap(), 0);
+        assert_eq!(matcher.splits, vec![0, 1, 2]);
+    }
+}
@@ -0,0 +1,370 @@
+use std::error::Error;
+
+use serde::{Deserialize, Serialize};
+
+/// A rectangle on the canvas or in a video
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Area {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32
+}
+
+/// How the tiles are placed on the canvas
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Layout {
+    /// All tiles in a grid
+    Grid(Grid),
+    /// The featured tile next to a strip of the other tiles along one edge
+    Strip(Featured),
+    /// The featured tile on the whole canvas with the other tiles as insets
+    /// along one edge
+    Pip(Featured)
+}
+
+/// Grid of tiles. Without columns and rows, the grid is chosen to be as
+/// square as possible.
+#[derive(Debug, Copy, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Grid {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub columns: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rows: Option<u32>,
+    pub fill: FillOrder
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FillOrder {
+    #[default]
+    Rows,
+    Columns
+}
+
+/// Settings of the layouts with a featured tile
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Featured {
+    /// Index of the featured input, starting at 0
+    pub featured: usize,
+    pub edge: Edge,
+    /// Size of the other tiles as a fraction of the canvas
+    pub size: f64
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Edge {
+    Top,
+    Bottom,
+    Left,
+    #[default]
+    Right
+}
+
+impl Layout {
+    /// Divides `area` into a box for each of the `n` tiles.
+    pub fn boxes(&self, n: u32, area: Area) -> Result<Vec<Area>, Box<dyn Error>> {
+        match self {
+            Layout::Grid(grid) => grid.boxes(n, area),
+            Layout::Strip(featured) => featured.strip_boxes(n, area),
+            Layout::Pip(featured) => featured.pip_boxes(n, area)
+        }
+    }
+
+    /// The featured input, which has to be drawn below the others.
+    pub fn featured(&self) -> Option<usize> {
+        match self {
+            Layout::Grid(_) => None,
+            Layout::Strip(featured) | Layout::Pip(featured) => Some(featured.featured)
+        }
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Layout {
+        Layout::Grid(Grid::default())
+    }
+}
+
+impl Grid {
+    /// Divides `area` into a box for each of the `n` tiles. The last row (or
+    /// column) is centered if it is not full.
+    pub fn boxes(&self, n: u32, area: Area) -> Result<Vec<Area>, Box<dyn Error>> {
+        if self.columns == Some(0) || self.rows == Some(0) {
+            Err("grid needs at least one column and row")?;
+        }
+        let (columns, rows, box_width, box_height) =
+            match (self.columns, self.rows) {
+                (None, None) => {
+                    let columns = (1..).find(|i| i * i >= n).unwrap();
+                    let rows = (1..).find(|i| i * columns >= n).unwrap();
+                    (columns, rows, area.width / columns, area.height / columns)
+                },
+                (Some(columns), rows) => {
+                    let rows = rows.unwrap_or_else(|| (1..).find(|i| i * columns >= n).unwrap());
+                    (columns, rows, area.width / columns, area.height / rows)
+                },
+                (None, Some(rows)) => {
+                    let columns = (1..).find(|i| i * rows >= n).unwrap();
+                    (columns, rows, area.width / columns, area.height / rows)
+                }
+            };
+        if columns * rows < n {
+            Err(format!("{}x{} grid has not enough space for {} inputs", columns, rows, n))?;
+        }
+
+        let per_line = match self.fill {
+            FillOrder::Rows => columns,
+            FillOrder::Columns => rows
+        };
+        let lines = (1..).find(|i| i * per_line >= n).unwrap();
+
+        let res = (0..n)
+            .map(|i| {
+                let line = i / per_line;
+                let pos = i % per_line;
+                let in_line =
+                    if line == lines - 1 {
+                        n - line * per_line
+                    } else {
+                        per_line
+                    };
+
+                let (x, y) = match self.fill {
+                    FillOrder::Rows => (
+                        area.width / 2 - in_line * box_width / 2 + pos * box_width,
+                        area.height / 2 - lines * box_height / 2 + line * box_height
+                    ),
+                    FillOrder::Columns => (
+                        area.width / 2 - lines * box_width / 2 + line * box_width,
+                        area.height / 2 - in_line * box_height / 2 + pos * box_height
+                    )
+                };
+
+                Area {
+                    x: area.x + x,
+                    y: area.y + y,
+                    width: box_width,
+                    height: box_height
+                }
+            })
+            .collect();
+
+        Ok(res)
+    }
+}
+
+impl Featured {
+    fn check(&self, n: u32) -> Result<(), Box<dyn Error>> {
+        if self.featured >= n as usize {
+            Err(format!("featured input {} does not exist", self.featured))?;
+        }
+        if !(self.size > 0.0 && self.size < 1.0) {
+            Err(format!("invalid tile size: {}", self.size))?;
+        }
+        Ok(())
+    }
+
+    /// The featured tile gets `area` minus a strip along the edge, which is
+    /// divided equally among the other tiles.
+    fn strip_boxes(&self, n: u32, area: Area) -> Result<Vec<Area>, Box<dyn Error>> {
+        self.check(n)?;
+        if n == 1 {
+            return Ok(vec![area]);
+        }
+
+        let others = n - 1;
+        let (main, strip) = match self.edge {
+            Edge::Top | Edge::Bottom => {
+                let size = (area.height as f64 * self.size) as u32;
+                let main = Area { height: area.height - size, ..area };
+                let strip = Area { height: size, ..area };
+                if self.edge == Edge::Top {
+                    (Area { y: area.y + size, ..main }, strip)
+                } else {
+                    (main, Area { y: area.y + area.height - size, ..strip })
+                }
+            },
+            Edge::Left | Edge::Right => {
+                let size = (area.width as f64 * self.size) as u32;
+                let main = Area { width: area.width - size, ..area };
+                let strip = Area { width: size, ..area };
+                if self.edge == Edge::Left {
+                    (Area { x: area.x + size, ..main }, strip)
+                } else {
+                    (main, Area { x: area.x + area.width - size, ..strip })
+                }
+            }
+        };
+
+        let mut other_boxes = (0..others).map(|i| {
+            match self.edge {
+                Edge::Top | Edge::Bottom => {
+                    let width = strip.width / others;
+                    Area { x: strip.x + i * width, width, ..strip }
+                },
+                Edge::Left | Edge::Right => {
+                    let height = strip.height / others;
+                    Area { y: strip.y + i * height, height, ..strip }
+                }
+            }
+        });
+
+        Ok((0..n as usize)
+            .map(|i| if i == self.featured { main } else { other_boxes.next().unwrap() })
+            .collect())
+    }
+
+    /// The featured tile gets all of `area`, the other tiles are placed on
+    /// top of it in a row along the edge, starting at the bottom right.
+    fn pip_boxes(&self, n: u32, area: Area) -> Result<Vec<Area>, Box<dyn Error>> {
+        self.check(n)?;
+        if n == 1 {
+            return Ok(vec![area]);
+        }
+
+        let others = n - 1;
+        let margin = area.width.min(area.height) / 40;
+        let (mut width, mut height) = (
+            (area.width as f64 * self.size) as u32,
+            (area.height as f64 * self.size) as u32
+        );
+        match self.edge {
+            Edge::Top | Edge::Bottom => {
+                let max = (area.width - margin) / others - margin;
+                if width > max {
+                    height = height * max / width;
+                    width = max;
+                }
+            },
+            Edge::Left | Edge::Right => {
+                let max = (area.height - margin) / others - margin;
+                if height > max {
+                    width = width * max / height;
+                    height = max;
+                }
+            }
+        }
+
+        let mut other_boxes = (0..others).map(|i| {
+            let (x, y) = match self.edge {
+                Edge::Top | Edge::Bottom => (
+                    area.width - (i + 1) * (width + margin),
+                    if self.edge == Edge::Top { margin } else { area.height - height - margin }
+                ),
+                Edge::Left | Edge::Right => (
+                    if self.edge == Edge::Left { margin } else { area.width - width - margin },
+                    area.height - (i + 1) * (height + margin)
+                )
+            };
+            Area { x: area.x + x, y: area.y + y, width, height }
+        });
+
+        Ok((0..n as usize)
+            .map(|i| if i == self.featured { area } else { other_boxes.next().unwrap() })
+            .collect())
+    }
+}
+
+impl Default for Featured {
+    fn default() -> Featured {
+        Featured {
+            featured: 0,
+            edge: Edge::default(),
+            size: 0.25
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CANVAS: Area = Area { x: 10, y: 20, width: 1200, height: 600 };
+
+    fn positions(boxes: &[Area]) -> Vec<(u32, u32)> {
+        boxes.iter().map(|area| (area.x, area.y)).collect()
+    }
+
+    #[test]
+    fn square_grid() {
+        let boxes = Grid::default().boxes(4, CANVAS).unwrap();
+        assert!(boxes.iter().all(|area| area.width == 600 && area.height == 300));
+        assert_eq!(positions(&boxes), vec![(10, 20), (610, 20), (10, 320), (610, 320)]);
+    }
+
+    #[test]
+    fn partly_filled_last_row() {
+        let boxes = Grid::default().boxes(3, CANVAS).unwrap();
+        assert_eq!(positions(&boxes), vec![(10, 20), (610, 20), (310, 320)]);
+
+        // the two boxes of the last row are centered horizontally
+        let grid = Grid { columns: Some(3), ..Grid::default() };
+        let boxes = grid.boxes(5, CANVAS).unwrap();
+        assert!(boxes.iter().all(|area| area.width == 400 && area.height == 300));
+        assert_eq!(positions(&boxes), vec![(10, 20), (410, 20), (810, 20), (210, 320), (610, 320)]);
+    }
+
+    #[test]
+    fn fill_columns() {
+        let grid = Grid { rows: Some(2), fill: FillOrder::Columns, ..Grid::default() };
+        let boxes = grid.boxes(3, CANVAS).unwrap();
+        assert!(boxes.iter().all(|area| area.width == 600 && area.height == 300));
+        assert_eq!(positions(&boxes), vec![(10, 20), (10, 320), (610, 170)]);
+    }
+
+    #[test]
+    fn grid_too_small() {
+        let grid = Grid { columns: Some(2), rows: Some(1), ..Grid::default() };
+        assert!(grid.boxes(3, CANVAS).is_err());
+    }
+
+    #[test]
+    fn grid_without_columns_or_rows() {
+        let grid = Grid { columns: Some(0), ..Grid::default() };
+        assert!(grid.boxes(3, CANVAS).is_err());
+        let grid = Grid { rows: Some(0), ..Grid::default() };
+        assert!(grid.boxes(3, CANVAS).is_err());
+    }
+
+    #[test]
+    fn strip() {
+        let featured = Featured { featured: 1, ..Featured::default() };
+        let boxes = Layout::Strip(featured).boxes(3, CANVAS).unwrap();
+        assert_eq!(boxes, vec![
+            Area { x: 910, y: 20, width: 300, height: 300 },
+            Area { x: 10, y: 20, width: 900, height: 600 },
+            Area { x: 910, y: 320, width: 300, height: 300 }
+        ]);
+    }
+
+    #[test]
+    fn pip() {
+        let boxes = Layout::Pip(Featured::default()).boxes(3, CANVAS).unwrap();
+        assert_eq!(boxes, vec![
+            CANVAS,
+            Area { x: 895, y: 455, width: 300, height: 150 },
+            Area { x: 895, y: 290, width: 300, height: 150 }
+        ]);
+    }
+
+    #[test]
+    fn pip_shrinks_insets_to_fit() {
+        let featured = Featured { edge: Edge::Bottom, size: 0.5, ..Featured::default() };
+        let boxes = Layout::Pip(featured).boxes(4, CANVAS).unwrap();
+        // 3 insets of at most (1200 - 15) / 3 - 15 = 380 pixels wide
+        assert!(boxes[1..].iter().all(|area| area.width == 380 && area.height == 190));
+        assert!(boxes[1..].iter().all(|area| area.y + area.height == CANVAS.y + CANVAS.height - 15));
+    }
+
+    #[test]
+    fn featured_input() {
+        let featured = Featured { featured: 2, ..Featured::default() };
+        assert!(Layout::Strip(featured).boxes(2, CANVAS).is_err());
+        assert_eq!(Layout::Pip(Featured::default()).boxes(1, CANVAS).unwrap(), vec![CANVAS]);
+    }
+}
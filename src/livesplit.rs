@@ -0,0 +1,187 @@
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use roxmltree::{Document, Node};
+
+use crate::splitscreen::{RunSplit, parse_split_time};
+use crate::splitsio::TimingMethod;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Attempt {
+    PersonalBest,
+    Id(u32)
+}
+
+pub fn read_splits(path: &Path, attempt: Attempt, timing: TimingMethod) -> Result<Vec<RunSplit>, Box<dyn Error>> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("cannot open {}: {}", path.display(), e))?;
+    parse_splits(&text, attempt, timing)
+        .map_err(|e| format!("{}: {}", path.display(), e).into())
+}
+
+/// Parses the splits of `attempt` from the contents of a LiveSplit file.
+fn parse_splits(text: &str, attempt: Attempt, timing: TimingMethod) -> Result<Vec<RunSplit>, Box<dyn Error>> {
+    let doc = Document::parse(text)?;
+
+    let run = doc.root_element();
+    if !run.has_tag_name("Run") {
+        Err("not a LiveSplit file")?;
+    }
+
+    if let Attempt::Id(id) = attempt {
+        let found = child(run, "AttemptHistory").iter()
+            .flat_map(|node| node.children())
+            .any(|node| node.has_tag_name("Attempt") && node.attribute("id") == Some(&id.to_string()));
+        if !found {
+            Err(format!("no attempt with id {}", id))?;
+        }
+    }
+
+    let segments = child(run, "Segments")
+        .ok_or("missing segments")?;
+
+    let mut res = Vec::new();
+    let mut time = 0.0;
+    for segment in segments.children().filter(|node| node.has_tag_name("Segment")) {
+        let name = child(segment, "Name")
+            .and_then(|node| node.text())
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+        let name_str = name.as_deref().unwrap_or("<unnamed>");
+
+        match attempt {
+            Attempt::PersonalBest => {
+                let split_time = child(segment, "SplitTimes").iter()
+                    .flat_map(|node| node.children())
+                    .find(|node| node.has_tag_name("SplitTime") && node.attribute("name") == Some("Personal Best"))
+                    .and_then(|node| time_of(node, timing))
+                    .ok_or_else(|| format!("personal best has no {} time for segment `{}`",
+                        timing, name_str))?;
+                time = parse_split_time(split_time)?;
+            },
+            Attempt::Id(id) => {
+                let segment_time = child(segment, "SegmentHistory").iter()
+                    .flat_map(|node| node.children())
+                    .find(|node| node.has_tag_name("Time") && node.attribute("id") == Some(&id.to_string()))
+                    .and_then(|node| time_of(node, timing))
+                    .ok_or_else(|| format!("attempt {} has no {} time for segment `{}`",
+                        id, timing, name_str))?;
+                time += parse_split_time(segment_time)?;
+            }
+        }
+
+        res.push(RunSplit { name, time });
+    }
+
+    if res.is_empty() {
+        Err("no segments")?;
+    }
+
+    Ok(res)
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|node| node.has_tag_name(name))
+}
+
+fn time_of<'a>(node: Node<'a, '_>, timing: TimingMethod) -> Option<&'a str> {
+    let name = match timing {
+        TimingMethod::RealTime => "RealTime",
+        TimingMethod::GameTime => "GameTime"
+    };
+    child(node, name)
+        .and_then(|node| node.text())
+        .map(|s| s.trim())
+}
+
+impl FromStr for Attempt {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Attempt, String> {
+        if s.eq_ignore_ascii_case("pb") {
+            Ok(Attempt::PersonalBest)
+        } else {
+            s.parse().map(Attempt::Id)
+                .map_err(|_| format!("invalid attempt: {}", s))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUN: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Run version="1.7.0">
+  <AttemptHistory>
+    <Attempt id="1" />
+    <Attempt id="2" />
+  </AttemptHistory>
+  <Segments>
+    <Segment>
+      <Name>Forest</Name>
+      <SplitTimes>
+        <SplitTime name="Personal Best">
+          <RealTime>00:01:23.4560000</RealTime>
+          <GameTime>00:01:20.0000000</GameTime>
+        </SplitTime>
+      </SplitTimes>
+      <SegmentHistory>
+        <Time id="1"><RealTime>00:01:30.0000000</RealTime></Time>
+        <Time id="2"><RealTime>00:01:40.0000000</RealTime></Time>
+      </SegmentHistory>
+    </Segment>
+    <Segment>
+      <Name> </Name>
+      <SplitTimes>
+        <SplitTime name="Personal Best">
+          <RealTime>00:02:50.0000000</RealTime>
+        </SplitTime>
+      </SplitTimes>
+      <SegmentHistory>
+        <Time id="1"><RealTime>00:01:30.5000000</RealTime></Time>
+      </SegmentHistory>
+    </Segment>
+  </Segments>
+</Run>"#;
+
+    fn times(splits: &[RunSplit]) -> Vec<(Option<&str>, f64)> {
+        splits.iter().map(|split| (split.name.as_deref(), split.time)).collect()
+    }
+
+    #[test]
+    fn personal_best() {
+        let splits = parse_splits(RUN, Attempt::PersonalBest, TimingMethod::RealTime).unwrap();
+        assert_eq!(times(&splits), vec![(Some("Forest"), 83.456), (None, 170.0)]);
+    }
+
+    #[test]
+    fn attempt_adds_up_segment_times() {
+        let splits = parse_splits(RUN, Attempt::Id(1), TimingMethod::RealTime).unwrap();
+        assert_eq!(times(&splits), vec![(Some("Forest"), 90.0), (None, 180.5)]);
+    }
+
+    #[test]
+    fn missing_times() {
+        let err = parse_splits(RUN, Attempt::Id(2), TimingMethod::RealTime).unwrap_err();
+        assert_eq!(err.to_string(), "attempt 2 has no real time for segment `<unnamed>`");
+        let err = parse_splits(RUN, Attempt::PersonalBest, TimingMethod::GameTime).unwrap_err();
+        assert_eq!(err.to_string(), "personal best has no game time for segment `<unnamed>`");
+        let err = parse_splits(RUN, Attempt::Id(3), TimingMethod::RealTime).unwrap_err();
+        assert_eq!(err.to_string(), "no attempt with id 3");
+    }
+
+    #[test]
+    fn not_a_run() {
+        assert!(parse_splits("<Layout />", Attempt::PersonalBest, TimingMethod::RealTime).is_err());
+    }
+
+    #[test]
+    fn parse_attempt() {
+        assert_eq!("PB".parse::<Attempt>().unwrap(), Attempt::PersonalBest);
+        assert_eq!("12".parse::<Attempt>().unwrap(), Attempt::Id(12));
+        assert!("best".parse::<Attempt>().is_err());
+    }
+}
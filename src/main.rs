@@ -1,13 +1,28 @@
 use std::env;
 use std::error::Error;
+use std::fs::File;
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process;
 
-use clap::{AppSettings, Arg, ArgGroup, Command, command};
+use clap::{AppSettings, Arg, ArgGroup, ArgMatches, Command, command};
+use image::ImageFormat;
 
+mod banner;
+mod detect;
+mod layout;
+mod livesplit;
+mod panel;
+mod project;
+mod results;
 mod splitscreen;
-use splitscreen::{Config, Compare, Encoder, Input};
+mod splitsio;
+use banner::Banner;
+use detect::Reference;
+use layout::{Edge, Featured, FillOrder, Grid, Layout};
+use panel::{Panel, Side};
+use project::Project;
+use splitscreen::{Compare, Config, Encoder, Input, LabelPosition, Leader, Problems, Timer};
 
 fn main() {
     if let Err(err) = run() {
@@ -26,37 +41,92 @@ fn run() -> Result<(), Box<dyn Error>> {
             .about("List all available encoders")
         )
 
-        .subcommand(Command::new("render")
-            .about("Render comparison video")
+        .subcommand(input_args(Command::new("check")
+            .about("Check inputs and split files for problems")
+
+            .arg(project_arg())
+        ))
+
+        .subcommand(Command::new("detect-splits")
+            .about("Detect split times in a video and write them as a split file")
+
+            .group(ArgGroup::new("detect-mode")
+                .args(&["reference", "black"])
+                .required(true))
+            .arg(Arg::new("reference")
+                .long("ref")
+                .short('R')
+                .takes_value(true)
+                .multiple_values(true)
+                .value_name("IMAGE")
+                .help("Match each IMAGE, in order, against the frames following the previous split"))
+
+            .arg(Arg::new("threshold")
+                .long("threshold")
+                .value_name("SIMILARITY")
+                .help("Minimum similarity between 0 and 1 for a frame to match a reference image (default 0.92)"))
+
+            .arg(Arg::new("black")
+                .long("black")
+                .short('B')
+                .help("Split at the start of each run of dark frames, e.g. black transitions between levels"))
+            .arg(Arg::new("luma")
+                .long("luma")
+                .value_name("LEVEL")
+                .help("Maximum luma between 0 and 255 for a pixel to count as dark (default 32)"))
+            .arg(Arg::new("min-duration")
+                .long("min-duration")
+                .value_name("SECONDS")
+                .help("Minimum duration of a run of dark frames (default 0.2)"))
 
-            .arg(Arg::new("resolution")
-                .long("res")
-                .short('s')
+            .arg(Arg::new("output")
+                .long("out")
+                .short('o')
+                .value_name("FILENAME")
+                .help("Write split file to FILENAME instead of stdout"))
+
+            .arg(Arg::new("video")
+                .index(1)
                 .required(true)
-                .value_name("WIDTHxHEIGHT")
-                .help("Set resolution to WIDTHxHEIGHT"))
+                .value_name("VIDEO")
+                .help("Video to detect splits in"))
+        )
 
-            .arg(Arg::new("fps")
-                .long("fps")
-                .short('r')
+        .subcommand(input_args(config_args(Command::new("preview")
+            .about("Render a single frame of the comparison video as PNG")
+
+            .arg(project_arg())
+            )
+
+            .group(ArgGroup::new("position")
+                .args(&["frame", "split"]))
+            .arg(Arg::new("frame")
+                .long("frame")
+                .value_name("N")
+                .help("Render frame N, starting at 0 (default: 0)"))
+            .arg(Arg::new("split")
+                .long("split")
+                .value_name("N")
+                .help("Render the frame at which the last runner finishes split N, starting at 1"))
+
+            .arg(Arg::new("output")
+                .long("out")
+                .short('o')
                 .required(true)
-                .value_name("FPS")
-                .help("Set frame rate to FPS"))
-
-            .group(ArgGroup::new("cmp-type")
-                .args(&["cmp-loss", "cmp-save"]))
-            .arg(Arg::new("cmp-loss")
-                .long("cmp-loss")
-                .help("Compare time loss"))
-            .arg(Arg::new("cmp-save")
-                .long("cmp-save")
-                .help("Compare time save"))
-
-            .arg(Arg::new("pause")
-                .long("pause")
-                .short('p')
-                .value_name("SECONDS")
-                .help("Pause for SECONDS seconds after each split"))
+                .value_name("FILENAME")
+                .help("Save the frame as PNG to FILENAME"))
+        ))
+
+        .subcommand(input_args(config_args(Command::new("render")
+            .about("Render comparison video")
+
+            .arg(project_arg())
+
+            .arg(Arg::new("save-project")
+                .long("save-project")
+                .value_name("FILENAME")
+                .help("Save settings and inputs to the project file FILENAME instead of rendering"))
+            )
 
             .arg(Arg::new("output")
                 .long("out")
@@ -68,7 +138,7 @@ fn run() -> Result<(), Box<dyn Error>> {
                 .long("encoder")
                 .short('e')
                 .value_name("ENCODER")
-                .help("Use ENCODER for video encoding (one of x264 (default), vaapi, nvenc, amf, qsv)"))
+                .help("Use ENCODER for video encoding (one of x264 (default), vaapi, nvenc, amf, qsv, or prores, png, qtrle with alpha)"))
 
             .arg(Arg::new("raw")
                 .long("raw")
@@ -78,24 +148,11 @@ fn run() -> Result<(), Box<dyn Error>> {
                 .long("report")
                 .help("Report progress to stderr"))
 
-            .group(ArgGroup::new("input-type")
-                .args(&["input-files", "input-args"]))
-            .arg(Arg::new("input-files")
-                .long("input-files")
-                .short('F')
-                .help("Interpret INPUT as pairs of video and split files, i.e. INPUT = video1 splitfile1 video2 splifile2 ... (this is the default behavior)"))
-            .arg(Arg::new("input-args")
-                .long("input-args")
-                .short('A')
-                .help("Interpret INPUT as video files combined with arguments, seperated by `--`, i.e. INPUT = video1 arg arg ... -- video2 arg arg ... -- ..."))
-
-            .arg(Arg::new("input")
-                .index(1)
-                .multiple_occurrences(true)
-                .required(true)
-                .value_name("INPUT")
-                .help("Input (see above)"))
-        )
+            .arg(Arg::new("verbose")
+                .long("verbose")
+                .short('v')
+                .help("Print the settings and the computed layout and split frames to stderr"))
+        ))
 
         .get_matches();
 
@@ -113,103 +170,161 @@ fn run() -> Result<(), Box<dyn Error>> {
             }
         },
 
-        ("render", matches) => {
-            let res = matches.value_of("resolution").unwrap();
-            let res_split: Vec<_> = res.split("x").collect();
-            if res_split.len() != 2 {
-                Err(format!("invalid resolution: {}", res))?;
-            }
-            let width = res_split[0].parse()
-                .map_err(|_| format!("invalid resolution: {}", res))?;
-            let height = res_split[1].parse()
-                .map_err(|_| format!("invalid resolution: {}", res))?;
-
-            let fps_str = matches.value_of("fps").unwrap();
-            let fps = fps_str.parse()
-                .map_err(|_| format!("invalid frame rate: {}", fps_str))?;
-
-            let cmp =
-                if matches.is_present("cmp-loss") {
-                    Some(Compare::TimeLoss)
-                } else if matches.is_present("cmp-save") {
-                    Some(Compare::TimeSave)
+        ("check", matches) => {
+            let mut config =
+                if let Some(path) = matches.value_of("project") {
+                    Project::load(Path::new(path))?.config
                 } else {
-                    None
+                    Config::default()
                 };
 
-            let pause =
-                if let Some(s) = matches.value_of("pause") {
-                    s.parse().map_err(|_| format!("invalid number: {}", s))?
+            let mut problems = Vec::new();
+            if let Some(inputs) = read_inputs(matches) {
+                config.inputs.clear();
+                for input in inputs {
+                    match input {
+                        Ok(input) => config.inputs.push(input),
+                        Err(err) => match err.downcast_ref::<Problems>() {
+                            Some(Problems(list)) => problems.extend(list.iter().cloned()),
+                            None => problems.push(err.to_string())
+                        }
+                    }
+                }
+            }
+            let (found, warnings) = match config.check() {
+                Ok(res) => res,
+                Err(err) => {
+                    for problem in problems.iter() {
+                        println!("{}", problem);
+                    }
+                    return Err(err);
+                }
+            };
+            problems.extend(found);
+
+            for warning in warnings.iter() {
+                println!("warning: {}", warning);
+            }
+            for problem in problems.iter() {
+                println!("{}", problem);
+            }
+            if !problems.is_empty() {
+                Err(format!("found {} problem(s)", problems.len()))?;
+            }
+            eprintln!("no problems found");
+        },
+
+        ("detect-splits", matches) => {
+            let video_path = Path::new(matches.value_of("video").unwrap());
+
+            let splits =
+                if matches.is_present("black") {
+                    let luma =
+                        if let Some(s) = matches.value_of("luma") {
+                            s.parse().map_err(|_| format!("invalid luma: {}", s))?
+                        } else {
+                            32
+                        };
+
+                    let min_duration =
+                        if let Some(s) = matches.value_of("min-duration") {
+                            s.parse().map_err(|_| format!("invalid number: {}", s))?
+                        } else {
+                            0.2
+                        };
+
+                    detect::detect_black(video_path, luma, min_duration)?
                 } else {
-                    0.0
+                    let threshold =
+                        if let Some(s) = matches.value_of("threshold") {
+                            s.parse().map_err(|_| format!("invalid number: {}", s))?
+                        } else {
+                            0.92
+                        };
+
+                    let refs = matches.values_of("reference").unwrap()
+                        .map(|path| Reference::load(Path::new(path)))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    detect::detect_references(video_path, &refs, threshold)?
                 };
 
-            let output = matches.value_of("output");
+            if let Some(name) = matches.value_of("output") {
+                detect::write_splits(File::create(name)?, &splits)?;
+            } else {
+                detect::write_splits(io::stdout(), &splits)?;
+            }
+        },
 
-            let encoder =
-                if let Some(val) = matches.value_of("encoder") {
-                    *encoders.iter()
-                        .find(|e| e.to_string() == val)
-                        .ok_or_else(|| format!("unknown encoder: {}", val))?
+        ("preview", matches) => {
+            let config = read_project(matches)?.config;
+            let info = config.prepare()?;
+
+            let frame_idx =
+                if let Some(s) = matches.value_of("split") {
+                    let split = s.parse().map_err(|_| format!("invalid number: {}", s))?;
+                    info.split_frame(split)
+                        .ok_or_else(|| format!("split {} does not exist", split))?
+                } else if let Some(s) = matches.value_of("frame") {
+                    s.parse().map_err(|_| format!("invalid number: {}", s))?
                 } else {
-                    encoder_default
+                    0
                 };
 
-            let raw = matches.is_present("raw");
+            let output = matches.value_of("output").unwrap();
+            config.preview(&info, frame_idx)?
+                .save_with_format(output, ImageFormat::Png)
+                .map_err(|e| format!("cannot write {}: {}", output, e))?;
+        },
 
-            let report = matches.is_present("report");
+        ("render", matches) => {
+            let mut project = read_project(matches)?;
 
+            if let Some(output) = matches.value_of("output") {
+                project.output = Some(PathBuf::from(output));
+            }
 
+            if let Some(val) = matches.value_of("encoder") {
+                project.encoder = *encoders.iter()
+                    .find(|e| e.to_string() == val)
+                    .ok_or_else(|| format!("unknown encoder: {}", val))?;
+            }
 
-            let mut inputs = Vec::new();
-            if matches.is_present("input-args") {
-                let mut it = matches.values_of("input").unwrap();
-                while let Some(video_path) = it.next() {
-                    if video_path == "--" {
-                        continue;
-                    }
-                    let video_path = Path::new(video_path);
-                    let args = it.by_ref().take_while(|s| *s != "--");
-                    let input = Input::from_args(video_path, args.into_iter())?;
-                    inputs.push(input);
-                }
-            } else {
-                let mut it = matches.values_of("input").unwrap();
-                while let Some(video_path) = it.next() {
-                    let video_path = Path::new(video_path);
-                    if let Some(split_file) = it.next() {
-                        let input = Input::from_file(video_path, Path::new(split_file))?;
-                        inputs.push(input);
-                    } else {
-                        Err(format!("error: missing split file for video: {:?}", video_path))?;
-                    }
-                }
+            if matches.is_present("raw") {
+                project.raw = true;
             }
 
+            let report = matches.is_present("report");
+            let verbose = matches.is_present("verbose");
 
+            if let Some(path) = matches.value_of("save-project") {
+                project.save(Path::new(path))?;
+                return Ok(());
+            }
 
-            let config = Config {
-                width, height, fps, cmp, pause, inputs
-            };
+            let Project { output, encoder, raw, config } = project;
 
-            eprintln!("{:#?}", config);
+            if verbose {
+                eprintln!("{:#?}", config);
+            }
 
             let info = config.prepare()?;
 
-            eprintln!("{:#?}", info);
+            if verbose {
+                eprintln!("{:#?}", info);
+            }
 
             if let Some(name) = output {
                 if raw {
-                    if name == "-" {
+                    if name == Path::new("-") {
                         config.render_raw(&info, io::stdout(), report)?;
                     } else {
-                        config.render_raw_to_file(&info, Path::new(name), report)?;
+                        config.render_raw_to_file(&info, &name, report)?;
                     }
                 } else {
-                    if name == "-" {
+                    if name == Path::new("-") {
                         config.encode_to_stdout(&info, encoder, report)?;
                     } else {
-                        config.encode_to_file(&info, encoder, report, Path::new(name))?;
+                        config.encode_to_file(&info, encoder, report, &name)?;
                     }
                 }
 
@@ -222,3 +337,416 @@ fn run() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+fn project_arg<'a>() -> Arg<'a> {
+    Arg::new("project")
+        .long("project")
+        .value_name("FILENAME")
+        .help("Load settings and inputs from the project file FILENAME (TOML or JSON), which can be overridden by the other options")
+}
+
+/// Adds the options that override the render settings of a project.
+fn config_args(cmd: Command) -> Command {
+    cmd
+        .arg(Arg::new("resolution")
+            .long("res")
+            .short('s')
+            .required_unless_present("project")
+            .value_name("WIDTHxHEIGHT")
+            .help("Set resolution to WIDTHxHEIGHT"))
+
+        .arg(Arg::new("fps")
+            .long("fps")
+            .short('r')
+            .required_unless_present("project")
+            .value_name("FPS")
+            .help("Set frame rate to FPS"))
+
+        .group(ArgGroup::new("cmp-type")
+            .args(&["cmp-loss", "cmp-save", "cmp-total"]))
+        .arg(Arg::new("cmp-loss")
+            .long("cmp-loss")
+            .help("Compare time loss"))
+        .arg(Arg::new("cmp-save")
+            .long("cmp-save")
+            .help("Compare time save"))
+        .arg(Arg::new("cmp-total")
+            .long("cmp-total")
+            .help("Compare total time up to the last split to the best total time"))
+        .arg(Arg::new("reference")
+            .long("reference")
+            .value_name("N")
+            .help("Compare against the N-th input, starting at 0, instead of the first to finish, which shows its own time"))
+
+        .arg(Arg::new("pause")
+            .long("pause")
+            .short('p')
+            .value_name("SECONDS")
+            .help("Pause for SECONDS seconds after each split"))
+
+        .arg(Arg::new("preroll")
+            .long("preroll")
+            .value_name("SECONDS")
+            .help("Show up to SECONDS seconds of video before the start of the run"))
+
+        .arg(Arg::new("results")
+            .long("results")
+            .value_name("SECONDS")
+            .help("Show the final times, segment times and ranking for SECONDS seconds after the last split"))
+
+        .arg(Arg::new("labels")
+            .long("labels")
+            .value_name("POSITION")
+            .possible_values(["top", "bottom"])
+            .help("Show runner names at POSITION of each tile (top (default) or bottom)"))
+
+        .arg(Arg::new("layout")
+            .long("layout")
+            .value_name("LAYOUT")
+            .possible_values(["grid", "strip", "pip"])
+            .help("Place tiles in a grid (default), show the featured tile next to a strip of the others, or show the others as insets on top of the featured tile (picture-in-picture)"))
+        .arg(Arg::new("columns")
+            .long("columns")
+            .value_name("N")
+            .help("Arrange tiles in a grid with N columns"))
+        .arg(Arg::new("rows")
+            .long("rows")
+            .value_name("N")
+            .help("Arrange tiles in a grid with N rows"))
+        .arg(Arg::new("fill")
+            .long("fill")
+            .value_name("ORDER")
+            .possible_values(["rows", "columns"])
+            .help("Fill the grid row by row (default) or column by column"))
+        .arg(Arg::new("featured")
+            .long("featured")
+            .value_name("N")
+            .help("Feature the N-th input, starting at 0 (strip and pip layouts)"))
+        .arg(Arg::new("edge")
+            .long("edge")
+            .value_name("EDGE")
+            .possible_values(["top", "bottom", "left", "right"])
+            .help("Place the other tiles along EDGE (strip and pip layouts, default: right)"))
+        .arg(Arg::new("tile-size")
+            .long("tile-size")
+            .value_name("FRACTION")
+            .help("Size of the other tiles as a fraction of the video (strip and pip layouts, default: 0.25)"))
+
+        .arg(Arg::new("fit")
+            .long("fit")
+            .value_name("MODE")
+            .possible_values(["fit", "fill", "stretch"])
+            .help("Scale videos to fit into their tiles (default), to fill their tiles and crop the rest, or stretch them, which can be overridden per input"))
+
+        .arg(Arg::new("gap")
+            .long("gap")
+            .value_name("PIXELS")
+            .help("Leave PIXELS pixels of space between tiles"))
+        .arg(Arg::new("border")
+            .long("border")
+            .value_name("PIXELS")
+            .help("Draw a border of PIXELS pixels around each tile"))
+        .arg(Arg::new("border-color")
+            .long("border-color")
+            .value_name("COLOR")
+            .help("Set the border color to COLOR (#rrggbb or a name, default: white), which can be overridden per input"))
+        .arg(Arg::new("background")
+            .long("background")
+            .value_name("IMAGE")
+            .help("Draw IMAGE scaled to the resolution behind the tiles"))
+
+        .arg(Arg::new("leader")
+            .long("leader")
+            .help("Highlight the tile that finishes a segment first and mark it as 1st"))
+        .arg(Arg::new("leader-color")
+            .long("leader-color")
+            .value_name("COLOR")
+            .help("Highlight the leading tile in COLOR (default: gold)"))
+        .arg(Arg::new("leader-enlarge")
+            .long("leader-enlarge")
+            .value_name("FACTOR")
+            .help("Enlarge the leading tile by FACTOR, e.g. 1.2"))
+
+        .arg(Arg::new("placements")
+            .long("placements")
+            .value_name("ORDER")
+            .possible_values(["segment", "total", "both"])
+            .help("Show the finishing order of each segment, by total time, or both as badges on the tiles"))
+
+        .arg(Arg::new("timer")
+            .long("timer")
+            .value_name("TIMES")
+            .possible_values(["segment", "total", "both"])
+            .help("Show a running timer with the segment time, the time since the start of the run, or both on each tile"))
+        .arg(Arg::new("timer-precision")
+            .long("timer-precision")
+            .value_name("DIGITS")
+            .help("Show DIGITS decimal places on the running timer, at most 3 (default: 2)"))
+
+        .arg(Arg::new("alpha")
+            .long("alpha")
+            .help("Render with a transparent background, which needs an encoder with alpha"))
+        .arg(Arg::new("overlay")
+            .long("overlay")
+            .help("Render only labels, deltas, borders and the split table on a transparent background, e.g. to put over other footage in a video editor"))
+
+        .arg(Arg::new("banner")
+            .long("banner")
+            .help("Show the number and name of the current segment in a banner across the top"))
+        .arg(Arg::new("banner-size")
+            .long("banner-size")
+            .value_name("FRACTION")
+            .help("Height of the banner as a fraction of the video (default: 0.08)"))
+        .arg(Arg::new("banner-fade")
+            .long("banner-fade")
+            .value_name("SECONDS")
+            .help("Fade the segment name in and out over SECONDS seconds when the segment changes"))
+
+        .arg(Arg::new("panel")
+            .long("panel")
+            .value_name("SIDE")
+            .possible_values(["left", "right"])
+            .help("Show a split table with the segment and total times of each runner at SIDE"))
+        .arg(Arg::new("panel-size")
+            .long("panel-size")
+            .value_name("FRACTION")
+            .help("Width of the split table as a fraction of the video (default: 0.25)"))
+}
+
+/// Loads the project given on the command line, if any, and applies the
+/// render settings and inputs given on the command line.
+fn read_project(matches: &ArgMatches) -> Result<Project, Box<dyn Error>> {
+    let mut project =
+        if let Some(path) = matches.value_of("project") {
+            Project::load(Path::new(path))?
+        } else {
+            Project::default()
+        };
+    let config = &mut project.config;
+
+    if let Some(res) = matches.value_of("resolution") {
+        let res_split: Vec<_> = res.split("x").collect();
+        if res_split.len() != 2 {
+            Err(format!("invalid resolution: {}", res))?;
+        }
+        config.width = res_split[0].parse()
+            .map_err(|_| format!("invalid resolution: {}", res))?;
+        config.height = res_split[1].parse()
+            .map_err(|_| format!("invalid resolution: {}", res))?;
+    }
+    if config.width == 0 || config.height == 0 {
+        Err("missing resolution")?;
+    }
+
+    if let Some(fps_str) = matches.value_of("fps") {
+        config.fps = fps_str.parse()
+            .map_err(|_| format!("invalid frame rate: {}", fps_str))?;
+    }
+    if config.fps == 0 {
+        Err("missing frame rate")?;
+    }
+
+    if matches.is_present("cmp-loss") {
+        config.cmp = Some(Compare::TimeLoss);
+    } else if matches.is_present("cmp-save") {
+        config.cmp = Some(Compare::TimeSave);
+    } else if matches.is_present("cmp-total") {
+        config.cmp = Some(Compare::Cumulative);
+    }
+    if let Some(s) = matches.value_of("reference") {
+        config.reference = Some(s.parse().map_err(|_| format!("invalid number: {}", s))?);
+    }
+
+    if let Some(s) = matches.value_of("pause") {
+        config.pause = s.parse().map_err(|_| format!("invalid number: {}", s))?;
+    }
+
+    if let Some(s) = matches.value_of("preroll") {
+        config.preroll = s.parse().map_err(|_| format!("invalid number: {}", s))?;
+    }
+    if let Some(s) = matches.value_of("results") {
+        config.results = s.parse().map_err(|_| format!("invalid number: {}", s))?;
+    }
+
+    match matches.value_of("labels") {
+        Some("top") => config.labels = LabelPosition::Top,
+        Some("bottom") => config.labels = LabelPosition::Bottom,
+        _ => {}
+    }
+
+    let featured = match config.layout {
+        Layout::Strip(featured) | Layout::Pip(featured) => featured,
+        Layout::Grid(_) => Featured::default()
+    };
+    match matches.value_of("layout") {
+        Some("grid") if !matches!(config.layout, Layout::Grid(_)) =>
+            config.layout = Layout::Grid(Grid::default()),
+        Some("strip") => config.layout = Layout::Strip(featured),
+        Some("pip") => config.layout = Layout::Pip(featured),
+        _ => {}
+    }
+
+    match &mut config.layout {
+        Layout::Grid(grid) => {
+            if let Some(s) = matches.value_of("columns") {
+                grid.columns = Some(s.parse().ok().filter(|n| *n > 0)
+                    .ok_or_else(|| format!("invalid number of columns: {}", s))?);
+            }
+            if let Some(s) = matches.value_of("rows") {
+                grid.rows = Some(s.parse().ok().filter(|n| *n > 0)
+                    .ok_or_else(|| format!("invalid number of rows: {}", s))?);
+            }
+            match matches.value_of("fill") {
+                Some("rows") => grid.fill = FillOrder::Rows,
+                Some("columns") => grid.fill = FillOrder::Columns,
+                _ => {}
+            }
+            if ["featured", "edge", "tile-size"].iter().any(|arg| matches.is_present(arg)) {
+                Err("--featured, --edge and --tile-size need the strip or pip layout")?;
+            }
+        },
+        Layout::Strip(featured) | Layout::Pip(featured) => {
+            if let Some(s) = matches.value_of("featured") {
+                featured.featured = s.parse().map_err(|_| format!("invalid number: {}", s))?;
+            }
+            match matches.value_of("edge") {
+                Some("top") => featured.edge = Edge::Top,
+                Some("bottom") => featured.edge = Edge::Bottom,
+                Some("left") => featured.edge = Edge::Left,
+                Some("right") => featured.edge = Edge::Right,
+                _ => {}
+            }
+            if let Some(s) = matches.value_of("tile-size") {
+                featured.size = s.parse().map_err(|_| format!("invalid number: {}", s))?;
+            }
+            if ["columns", "rows", "fill"].iter().any(|arg| matches.is_present(arg)) {
+                Err("--columns, --rows and --fill need the grid layout")?;
+            }
+        }
+    }
+
+    if let Some(s) = matches.value_of("fit") {
+        config.fit = s.parse()?;
+    }
+
+    if let Some(s) = matches.value_of("gap") {
+        config.gap = s.parse().map_err(|_| format!("invalid number: {}", s))?;
+    }
+    if let Some(s) = matches.value_of("border") {
+        config.border = s.parse().map_err(|_| format!("invalid number: {}", s))?;
+    }
+    if let Some(s) = matches.value_of("border-color") {
+        config.border_color = s.parse()?;
+    }
+    if let Some(background) = matches.value_of("background") {
+        config.background = Some(PathBuf::from(background));
+    }
+
+    if matches.is_present("leader") {
+        config.leader.get_or_insert_with(Leader::default);
+    }
+    if let Some(s) = matches.value_of("leader-color") {
+        config.leader.get_or_insert_with(Leader::default).color = s.parse()?;
+    }
+    if let Some(s) = matches.value_of("leader-enlarge") {
+        config.leader.get_or_insert_with(Leader::default).enlarge =
+            s.parse().map_err(|_| format!("invalid number: {}", s))?;
+    }
+
+    if let Some(s) = matches.value_of("placements") {
+        config.placements = Some(s.parse()?);
+    }
+
+    if let Some(s) = matches.value_of("timer") {
+        config.timer.get_or_insert_with(Timer::default).show = s.parse()?;
+    }
+    if let Some(s) = matches.value_of("timer-precision") {
+        config.timer.get_or_insert_with(Timer::default).precision =
+            s.parse().map_err(|_| format!("invalid number: {}", s))?;
+    }
+
+    if matches.is_present("alpha") {
+        config.alpha = true;
+    }
+    if matches.is_present("overlay") {
+        config.overlay = true;
+    }
+
+    if matches.is_present("banner") {
+        config.banner.get_or_insert_with(Banner::default);
+    }
+    if let Some(s) = matches.value_of("banner-size") {
+        config.banner.get_or_insert_with(Banner::default).size =
+            s.parse().map_err(|_| format!("invalid number: {}", s))?;
+    }
+    if let Some(s) = matches.value_of("banner-fade") {
+        config.banner.get_or_insert_with(Banner::default).fade =
+            s.parse().map_err(|_| format!("invalid number: {}", s))?;
+    }
+
+    match matches.value_of("panel") {
+        Some("left") => config.panel.get_or_insert_with(Panel::default).side = Side::Left,
+        Some("right") => config.panel.get_or_insert_with(Panel::default).side = Side::Right,
+        _ => {}
+    }
+    if let Some(s) = matches.value_of("panel-size") {
+        config.panel.get_or_insert_with(Panel::default).size =
+            s.parse().map_err(|_| format!("invalid number: {}", s))?;
+    }
+
+    if let Some(inputs) = read_inputs(matches) {
+        config.inputs = inputs.into_iter().collect::<Result<_, _>>()?;
+    }
+    if config.inputs.is_empty() {
+        Err("missing inputs")?;
+    }
+
+    Ok(project)
+}
+
+fn input_args(cmd: Command) -> Command {
+    cmd
+        .group(ArgGroup::new("input-type")
+            .args(&["input-files", "input-args"]))
+        .arg(Arg::new("input-files")
+            .long("input-files")
+            .short('F')
+            .help("Interpret INPUT as pairs of video and split files, i.e. INPUT = video1 splitfile1 video2 splifile2 ... (this is the default behavior). LiveSplit (.lss) and splits.io (.json) files are imported as personal bests"))
+        .arg(Arg::new("input-args")
+            .long("input-args")
+            .short('A')
+            .help("Interpret INPUT as video files combined with arguments, seperated by `--`, i.e. INPUT = video1 arg arg ... -- video2 arg arg ... -- ..."))
+
+        .arg(Arg::new("input")
+            .index(1)
+            .multiple_occurrences(true)
+            .required_unless_present("project")
+            .value_name("INPUT")
+            .help("Input (see above)"))
+}
+
+/// Reads the inputs given on the command line, if any.
+fn read_inputs(matches: &ArgMatches) -> Option<Vec<Result<Input, Box<dyn Error>>>> {
+    let mut it = matches.values_of("input")?;
+    let mut res = Vec::new();
+    if matches.is_present("input-args") {
+        while let Some(video_path) = it.next() {
+            if video_path == "--" {
+                continue;
+            }
+            let video_path = Path::new(video_path);
+            let args = it.by_ref().take_while(|s| *s != "--");
+            res.push(Input::from_args(video_path, args.into_iter()));
+        }
+    } else {
+        while let Some(video_path) = it.next() {
+            let video_path = Path::new(video_path);
+            if let Some(split_file) = it.next() {
+                res.push(Input::from_file(video_path, Path::new(split_file)));
+            } else {
+                res.push(Err(format!("missing split file for video: {:?}", video_path).into()));
+            }
+        }
+    }
+    Some(res)
+}
@@ -0,0 +1,185 @@
+use std::error::Error;
+
+use image::Rgba;
+use imageproc::drawing::{draw_filled_rect_mut, draw_text_mut};
+use imageproc::rect::Rect;
+use rusttype::{Font, Scale};
+use serde::{Deserialize, Serialize};
+
+use crate::layout::Area;
+use crate::splitscreen::{Config, Frame, FramePixel, RenderInfo, format_time, text_bounds};
+
+/// Minimum height of a row of the split table in pixels
+const MIN_ROW_HEIGHT: u32 = 40;
+
+/// Maximum height of a row of the split table and the results table in
+/// pixels
+pub const MAX_ROW_HEIGHT: u32 = 72;
+
+/// Minimum width of the panel in pixels
+const MIN_WIDTH: u32 = 160;
+
+/// Colors shared by the panel, the banner and the results card
+pub const BACKGROUND: Rgba<u8> = Rgba([24, 24, 24, 255]);
+pub const HIGHLIGHT: Rgba<u8> = Rgba([48, 64, 112, 255]);
+pub const TEXT: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const TEXT_DIM: Rgba<u8> = Rgba([160, 160, 160, 255]);
+
+/// Side panel with a split table showing the segment and cumulative times
+/// of each runner.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Panel {
+    pub side: Side,
+    /// Width of the panel as a fraction of the video
+    pub size: f64
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Left,
+    #[default]
+    Right
+}
+
+impl Panel {
+    /// Splits `area` into the panel and the rest.
+    pub fn split(&self, area: Area) -> Result<(Area, Area), Box<dyn Error>> {
+        if !(self.size > 0.0 && self.size < 1.0) {
+            Err(format!("invalid panel size: {}", self.size))?;
+        }
+
+        let width = (area.width as f64 * self.size) as u32;
+        if width < MIN_WIDTH || area.height < 2 * MIN_ROW_HEIGHT {
+            Err(format!("panel is too small: {}x{}, needs at least {}x{}",
+                width, area.height, MIN_WIDTH, 2 * MIN_ROW_HEIGHT))?;
+        }
+        let panel = Area { width, ..area };
+        let rest = Area { width: area.width - width, ..area };
+        match self.side {
+            Side::Left =>
+                Ok((panel, Area { x: area.x + width, ..rest })),
+            Side::Right =>
+                Ok((Area { x: area.x + area.width - width, ..panel }, rest))
+        }
+    }
+
+    /// Draws the split table at `frame_idx`. Each row is a segment and each
+    /// column a runner, showing the cumulative time above the segment time.
+    /// Times of running segments are dimmed. If not all segments fit, only
+    /// the rows around the current segment are shown.
+    pub fn draw<P: FramePixel>(&self, frame: &mut Frame<P>, font: &Font, config: &Config, info: &RenderInfo, area: Area, frame_idx: u32) {
+        let mut tiles: Vec<_> = info.tiles.iter().collect();
+        tiles.sort_by_key(|tile| tile.input);
+
+        let n_segments = info.segments.len() as u32;
+        let rows = (area.height / MIN_ROW_HEIGHT).clamp(2, n_segments + 1);
+        let row_height = (area.height / rows).min(MAX_ROW_HEIGHT);
+        let visible = rows - 1;
+
+        let current = info.tiles[0].splits.iter()
+            .rposition(|(start, _)| *start <= frame_idx);
+        let first = match current {
+            Some(current) =>
+                (current as u32 + 1).saturating_sub(visible / 2 + 1).min(n_segments - visible),
+            None => 0
+        };
+
+        let name_width = area.width * 2 / (tiles.len() as u32 + 2);
+        let column_width = (area.width - name_width) / tiles.len() as u32;
+        let pad = (row_height / 8).max(2);
+        let scale = fit_scale(font, Scale::uniform(row_height as f32 * 0.4),
+            "00:00.000", column_width.saturating_sub(2 * pad));
+
+        // header with the runner names
+        for (i, tile) in tiles.iter().enumerate() {
+            let name = config.inputs[tile.input].name.clone()
+                .unwrap_or_else(|| format!("#{}", tile.input + 1));
+            let x = area.x + name_width + i as u32 * column_width;
+            draw_text_right(frame, font, scale, TEXT, x + column_width - pad, area.y + row_height / 4,
+                column_width.saturating_sub(2 * pad), &name);
+        }
+
+        for row in 0..visible {
+            let i = (first + row) as usize;
+            let y = area.y + (row + 1) * row_height;
+
+            if Some(i) == current {
+                draw_filled_rect_mut(frame,
+                    Rect::at(area.x as i32, y as i32).of_size(area.width, row_height),
+                    P::from_rgba(HIGHLIGHT)
+                );
+            }
+
+            let name = info.segments[i].clone()
+                .unwrap_or_else(|| format!("Segment {}", i + 1));
+            draw_text_left(frame, font, scale, TEXT, area.x + pad, y + row_height / 4,
+                name_width.saturating_sub(2 * pad), &name);
+
+            for (j, tile) in tiles.iter().enumerate() {
+                let (start, end) = tile.splits[i];
+                if frame_idx < start {
+                    continue;
+                }
+
+                let segment = frame_idx.min(end) - start;
+                let total: u32 = tile.splits[..i].iter()
+                    .map(|(start, end)| end - start)
+                    .sum::<u32>() + segment;
+                let color = if frame_idx >= end { TEXT } else { TEXT_DIM };
+
+                let x = area.x + name_width + j as u32 * column_width + column_width - pad;
+                let width = column_width.saturating_sub(2 * pad);
+                draw_text_right(frame, font, scale, color, x, y + pad,
+                    width, &format_time(total as f64 / config.fps as f64));
+                draw_text_right(frame, font, scale, TEXT_DIM, x, y + row_height / 2,
+                    width, &format_time(segment as f64 / config.fps as f64));
+            }
+        }
+    }
+}
+
+impl Default for Panel {
+    fn default() -> Panel {
+        Panel {
+            side: Side::default(),
+            size: 0.25
+        }
+    }
+}
+
+/// Shrinks `scale` so that `text` is at most `max_width` pixels wide.
+/// Draws the background of the panel or the banner, which does not change
+/// between frames.
+pub fn draw_background<P: FramePixel>(canvas: &mut Frame<P>, area: Area) {
+    draw_filled_rect_mut(canvas,
+        Rect::at(area.x as i32, area.y as i32).of_size(area.width, area.height),
+        P::from_rgba(BACKGROUND)
+    );
+}
+
+pub fn fit_scale(font: &Font, scale: Scale, text: &str, max_width: u32) -> Scale {
+    let (_, _, width, _) = text_bounds(font, scale, text);
+    if width > max_width {
+        Scale::uniform(scale.y * max_width as f32 / width as f32)
+    } else {
+        scale
+    }
+}
+
+/// Draws `text` with the top left of its line at (`x`, `y`).
+#[allow(clippy::too_many_arguments)]
+pub fn draw_text_left<P: FramePixel>(frame: &mut Frame<P>, font: &Font, scale: Scale, color: Rgba<u8>, x: u32, y: u32, max_width: u32, text: &str) {
+    let scale = fit_scale(font, scale, text, max_width);
+    draw_text_mut(frame, P::from_rgba(color), x, y, scale, font, text);
+}
+
+/// Draws `text` with the top right of its line at (`x`, `y`).
+#[allow(clippy::too_many_arguments)]
+pub fn draw_text_right<P: FramePixel>(frame: &mut Frame<P>, font: &Font, scale: Scale, color: Rgba<u8>, x: u32, y: u32, max_width: u32, text: &str) {
+    let scale = fit_scale(font, scale, text, max_width);
+    let (x_min, _, width, _) = text_bounds(font, scale, text);
+    let x = x as i32 - x_min - width as i32;
+    draw_text_mut(frame, P::from_rgba(color), x.max(0) as u32, y, scale, font, text);
+}
@@ -0,0 +1,91 @@
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::splitscreen::{Config, Encoder};
+
+/// A render description that can be stored as TOML or JSON. Relative paths
+/// are relative to the directory of the project file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Project {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<PathBuf>,
+    pub encoder: Encoder,
+    pub raw: bool,
+    #[serde(flatten)]
+    pub config: Config
+}
+
+impl Project {
+    pub fn load(path: &Path) -> Result<Project, Box<dyn Error>> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("cannot open {}: {}", path.display(), e))?;
+        let mut res: Project =
+            if is_json(path) {
+                serde_json::from_str(&text)
+                    .map_err(|e| format!("{}: {}", path.display(), e))?
+            } else {
+                toml::from_str(&text)
+                    .map_err(|e| format!("{}: {}", path.display(), e))?
+            };
+
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        if let Some(output) = &mut res.output {
+            if output != Path::new("-") {
+                *output = dir.join(&output);
+            }
+        }
+        if let Some(background) = &mut res.config.background {
+            *background = dir.join(&background);
+        }
+        for input in res.config.inputs.iter_mut() {
+            input.video_path = dir.join(&input.video_path);
+        }
+
+        Ok(res)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let cwd = env::current_dir()?;
+        let dir = cwd.join(path.parent().unwrap_or_else(|| Path::new("")));
+
+        let mut project = self.clone();
+        if let Some(output) = &mut project.output {
+            if output != Path::new("-") {
+                *output = relative_path(&cwd.join(&output), &dir);
+            }
+        }
+        if let Some(background) = &mut project.config.background {
+            *background = relative_path(&cwd.join(&background), &dir);
+        }
+        for input in project.config.inputs.iter_mut() {
+            input.video_path = relative_path(&cwd.join(&input.video_path), &dir);
+        }
+
+        let text =
+            if is_json(path) {
+                serde_json::to_string_pretty(&project)?
+            } else {
+                toml::to_string_pretty(&project)?
+            };
+        fs::write(path, text)
+            .map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+        Ok(())
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+}
+
+/// Makes the absolute `path` relative to `dir` if it is located inside of
+/// `dir`.
+fn relative_path(path: &Path, dir: &Path) -> PathBuf {
+    path.strip_prefix(dir)
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|_| path.to_path_buf())
+}
@@ -0,0 +1,89 @@
+use image::Rgba;
+use imageproc::drawing::{draw_filled_rect_mut, draw_text_mut};
+use imageproc::rect::Rect;
+use rusttype::{Font, Scale};
+
+use crate::layout::Area;
+use crate::panel::{BACKGROUND, HIGHLIGHT, MAX_ROW_HEIGHT, TEXT, draw_text_left, draw_text_right};
+use crate::splitscreen::{Config, Frame, FramePixel, RenderInfo, format_time, ordinal, place_color, text_bounds};
+
+/// Draws the results card shown after the last split. Runners are ordered by
+/// their final time, with a row for the time of each segment and the final
+/// time at the bottom. The fastest time of each segment is shown in gold.
+/// The background stays transparent when rendering with alpha.
+pub fn draw_results<P: FramePixel>(frame: &mut Frame<P>, font: &Font, config: &Config, info: &RenderInfo) {
+    let (width, height) = frame.dimensions();
+    let background = if config.has_alpha() { Rgba([0, 0, 0, 0]) } else { BACKGROUND };
+    draw_filled_rect_mut(frame, Rect::at(0, 0).of_size(width, height), P::from_rgba(background));
+
+    let last = info.segments.len() - 1;
+    let mut tiles: Vec<_> = (0..info.tiles.len()).collect();
+    tiles.sort_by_key(|i| (info.total_place(*i, last), info.tiles[*i].input));
+
+    let margin = height / 20;
+    let area = Area { x: margin, y: margin, width: width - 2 * margin, height: height - 2 * margin };
+
+    // title, header, segments and total
+    let rows = info.segments.len() as u32 + 3;
+    let row_height = (area.height / rows).min(MAX_ROW_HEIGHT);
+    let area = Area { y: area.y + (area.height - rows * row_height) / 2, height: rows * row_height, ..area };
+    let name_width = area.width * 2 / (tiles.len() as u32 + 2);
+    let column_width = (area.width - name_width) / tiles.len() as u32;
+    let pad = (row_height / 8).max(2);
+    let scale = Scale::uniform(row_height as f32 * 0.4);
+    let time_width = column_width.saturating_sub(2 * pad);
+    let column_x = |j: usize| area.x + name_width + (j as u32 + 1) * column_width - pad;
+    let to_seconds = |frames: u32| frames as f64 / config.fps as f64;
+
+    let title = "Results";
+    let title_scale = Scale::uniform(row_height as f32 * 0.7);
+    let (x_min, y_min, title_width, title_height) = text_bounds(font, title_scale, title);
+    let x = (area.x + area.width / 2) as i32 - title_width as i32 / 2 - x_min;
+    let y = (area.y + row_height / 2) as i32 - title_height as i32 / 2 - y_min;
+    draw_text_mut(frame, P::from_rgba(TEXT), x.max(0) as u32, y.max(0) as u32, title_scale, font, title);
+
+    // header with the placement and name of each runner
+    let y = area.y + row_height;
+    for (j, i) in tiles.iter().enumerate() {
+        let input = info.tiles[*i].input;
+        let place = info.total_place(*i, last);
+        let name = config.inputs[input].name.clone()
+            .unwrap_or_else(|| format!("#{}", input + 1));
+        draw_text_right(frame, font, scale, place_color(place), column_x(j), y + pad,
+            time_width, &ordinal(place));
+        draw_text_right(frame, font, scale, TEXT, column_x(j), y + row_height / 2,
+            time_width, &name);
+    }
+
+    for (split_idx, segment) in info.segments.iter().enumerate() {
+        let y = area.y + (split_idx as u32 + 2) * row_height;
+        let name = segment.clone()
+            .unwrap_or_else(|| format!("Segment {}", split_idx + 1));
+        draw_text_left(frame, font, scale, TEXT, area.x + pad, y + row_height / 4,
+            name_width.saturating_sub(2 * pad), &name);
+
+        let time = |i: usize| {
+            let (start, end) = info.tiles[i].splits[split_idx];
+            end - start
+        };
+        let best = tiles.iter().map(|i| time(*i)).min().unwrap();
+        for (j, i) in tiles.iter().enumerate() {
+            let color = if time(*i) == best { place_color(1) } else { TEXT };
+            draw_text_right(frame, font, scale, color, column_x(j), y + row_height / 4,
+                time_width, &format_time(to_seconds(time(*i))));
+        }
+    }
+
+    let y = area.y + (rows - 1) * row_height;
+    draw_filled_rect_mut(frame,
+        Rect::at(area.x as i32, y as i32).of_size(area.width, row_height),
+        P::from_rgba(HIGHLIGHT)
+    );
+    draw_text_left(frame, font, scale, TEXT, area.x + pad, y + row_height / 4,
+        name_width.saturating_sub(2 * pad), "Total");
+    for (j, i) in tiles.iter().enumerate() {
+        let total = info.tiles[*i].total_time(last);
+        draw_text_right(frame, font, scale, place_color(info.total_place(*i, last)),
+            column_x(j), y + row_height / 4, time_width, &format_time(to_seconds(total)));
+    }
+}
@@ -1,47 +1,236 @@
 use std::error::Error;
 use std::fmt;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{self, BufRead, BufReader, Read, Write};
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::str::FromStr;
 use std::sync::mpsc;
 use std::thread;
 
 use font_loader::system_fonts;
-use image::{GenericImage, RgbImage, Rgb};
+use image::{GenericImage, ImageBuffer, Pixel, Rgb, RgbaImage, Rgba};
+use image::imageops::{self, FilterType};
 use imageproc::drawing::{draw_filled_rect_mut, draw_text_mut};
 use imageproc::rect::Rect;
 use rusttype::{Font, Scale, point};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-#[derive(Debug, Clone)]
+use crate::layout::{Area, Layout};
+use crate::livesplit::{self, Attempt};
+use crate::banner::Banner;
+use crate::panel::{self, Panel};
+use crate::results::draw_results;
+use crate::splitsio::{self, TimingMethod};
+
+/// Range of frame rates of input videos that are considered valid
+const MIN_FRAME_RATE: f64 = 1.0;
+const MAX_FRAME_RATE: f64 = 500.0;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
     pub width: u32,
     pub height: u32,
     pub fps: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cmp: Option<Compare>,
+    /// Index of the input that deltas are measured against, starting at 0.
+    /// Without a reference, the first tile to finish is the baseline.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference: Option<usize>,
     pub pause: f64,
+    pub preroll: f64,
+    /// Duration of the results card after the last split in seconds, 0 for
+    /// none
+    pub results: f64,
+    pub labels: LabelPosition,
+    pub layout: Layout,
+    pub fit: FitMode,
+    /// Space between tiles in pixels
+    pub gap: u32,
+    /// Width of the tile borders in pixels
+    pub border: u32,
+    pub border_color: Color,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub banner: Option<Banner>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub panel: Option<Panel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub leader: Option<Leader>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placements: Option<Placements>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timer: Option<Timer>,
+    /// Render with a transparent background
+    pub alpha: bool,
+    /// Render only labels, deltas, borders and the split table on a
+    /// transparent background
+    pub overlay: bool,
     pub inputs: Vec<Input>
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Encoder {
+    #[default]
     X264,
     VAAPI,
     NVENC,
     AMF,
-    QSV
+    QSV,
+    ProRes,
+    PNG,
+    QTRLE
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub enum Compare {
+    #[serde(rename = "loss")]
     TimeLoss,
-    TimeSave
+    #[serde(rename = "save")]
+    TimeSave,
+    /// Difference of the total time to the best total time
+    #[serde(rename = "total")]
+    Cumulative
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LabelPosition {
+    #[default]
+    Top,
+    Bottom
+}
+
+/// Emphasis of the tile that finished a segment first
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Leader {
+    pub color: Color,
+    /// Factor by which the tile is enlarged, 1 to keep its size
+    pub enlarge: f64
+}
+
+/// Which finishing orders are shown as badges after a segment
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Placements {
+    /// Placement in the segment
+    Segment,
+    /// Placement by the total time up to the segment
+    Total,
+    Both
+}
+
+/// Running timer in the corner of each tile
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Timer {
+    pub show: TimerMode,
+    /// Number of decimal places, at most 3
+    pub precision: u32
+}
+
+/// Which times the running timer shows
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimerMode {
+    /// Time of the current segment
+    Segment,
+    /// Time since the start of the run
+    Total,
+    #[default]
+    Both
+}
+
+/// How a video is sized to its tile
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FitMode {
+    /// Scale to fit into the tile, keeping the aspect ratio
+    #[default]
+    Fit,
+    /// Scale to fill the tile, keeping the aspect ratio, and crop the rest
+    Fill,
+    /// Scale to the size of the tile
+    Stretch
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Input {
+    #[serde(rename = "video")]
     pub video_path: PathBuf,
-    pub splits: Vec<f64>
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<SplitTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<SplitTime>,
+    /// Region of the video that is shown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crop: Option<Area>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fit: Option<FitMode>,
+    /// Border color of the tile instead of the configured one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub border: Option<Color>,
+    #[serde(default)]
+    pub splits: Vec<Split>,
+    #[serde(skip)]
+    pub source: Source
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Split {
+    pub time: SplitTime,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip)]
+    pub line: Option<usize>
+}
+
+/// Where an input was read from, for error messages. Lines are argument
+/// numbers if the input was not read from a file.
+#[derive(Debug, Clone, Default)]
+pub struct Source {
+    pub path: Option<PathBuf>,
+    pub start_line: Option<usize>,
+    pub end_line: Option<usize>,
+    pub crop_line: Option<usize>
+}
+
+/// All problems found while reading an input, one per line when displayed
+#[derive(Debug, Clone)]
+pub struct Problems(pub Vec<String>);
+
+/// A split of an imported run
+#[derive(Debug, Clone)]
+pub struct RunSplit {
+    pub name: Option<String>,
+    /// Split time relative to the start of the run
+    pub time: f64
+}
+
+/// An RGB color, written as `#rrggbb` or a color name
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Color(pub [u8; 3]);
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SplitTime {
+    Seconds(f64),
+    Frame(u64)
+}
+
+#[derive(Debug, Clone)]
+pub struct VideoInfo {
+    pub width: u32,
+    pub height: u32,
+    pub frame_rate: (u32, u32),
+    pub duration: f64
 }
 
 #[derive(Debug, Clone)]
@@ -49,7 +238,12 @@ pub struct RenderInfo {
     pub start: u32,
     pub length: u32,
     pub tiles: Vec<RenderTileInfo>,
-    pub pauses: Vec<u32>
+    pub pauses: Vec<u32>,
+    pub segments: Vec<Option<String>>,
+    pub banner: Option<Area>,
+    pub panel: Option<Area>,
+    /// First frame of the results card
+    pub results: Option<u32>
 }
 
 #[derive(Debug, Clone)]
@@ -61,137 +255,234 @@ pub struct RenderTileInfo {
     pub y: u32,
     pub width: u32,
     pub height: u32,
+    pub fit: FitMode,
     pub splits: Vec<(u32, u32)>
 }
 
+/// Image with the pixel type of the rendered frames
+pub type Frame<P> = ImageBuffer<P, Vec<u8>>;
+
+/// Pixel type of the rendered frames, `Rgba<u8>` with transparency and
+/// `Rgb<u8>` without. Colors are given as `Rgba<u8>` and converted.
+pub trait FramePixel: Pixel<Subpixel = u8> + Send + 'static {
+    /// Matching ffmpeg pixel format
+    const FORMAT: &'static str;
+
+    fn from_rgba(color: Rgba<u8>) -> Self;
+}
+
+/// Text shown below a tile when comparing
+#[derive(Debug, Copy, Clone)]
+enum Delta {
+    /// Difference in frames to the baseline
+    Diff(i64),
+    /// Own time in frames of the reference tile
+    Time(u32)
+}
+
 
 
 impl Config {
     pub fn prepare(&self) -> Result<RenderInfo, Box<dyn Error>> {
-        let ffprobe = find_exec("ffprobe").ok_or("ffprobe not found")?;
-
-        let n_splits = self.inputs[0].splits.len();
+        let mut starts = Vec::new();
+        let mut runs = Vec::new();
         for input in &self.inputs {
-            if input.splits.len() != n_splits {
-                Err("inputs must have equal number of splits")?;
-            }
+            let (start, splits) = input.run()?;
+            starts.push(start);
+            runs.push(splits);
         }
+
+        let (splits, warnings) = match_splits(&self.inputs, &runs)?;
+        for warning in warnings {
+            eprintln!("warning: {}", warning);
+        }
+        let n_splits = splits[0].len();
         if n_splits == 0 {
             Err("inputs need at least one split")?;
         }
+        let segments = (0..n_splits)
+            .map(|i| splits.iter().find_map(|splits| splits[i].name.clone()))
+            .collect();
 
-        let mut inputs = Vec::new();
-        for input in &self.inputs {
-            let mut ffprobe = Command::new(&ffprobe)
-                .arg("-select_streams").arg("v:0")
-                .arg("-show_entries").arg("stream=width,height,duration")
-                .arg("-of").arg("default=nw=1:nk=1")
-                .arg(&input.video_path)
-                .stdin(Stdio::null())
-                .stdout(Stdio::piped())
-                .stderr(Stdio::null())
-                .spawn()?;
-
-            let lines: Vec<_> = BufReader::new(ffprobe.stdout.take().unwrap())
-                .lines().take(3).collect::<Result<_, _>>()?;
-            if lines.len() != 3 {
-                Err(format!("invalid video file: {:?}", input.video_path))?;
+        if let Some(timer) = &self.timer {
+            if timer.precision > 3 {
+                Err(format!("invalid timer precision: {}", timer.precision))?;
+            }
+        }
+        if let Some(reference) = self.reference {
+            if self.cmp.is_none() {
+                Err("--reference requires a comparison mode")?;
+            }
+            if reference >= self.inputs.len() {
+                Err(format!("reference input {} does not exist", reference))?;
             }
-
-            let width: u32 = lines[0].parse()?;
-            let height: u32 = lines[1].parse()?;
-            let time: f64 = lines[2].parse()?;
-
-            inputs.push((width, height, input.splits[0], time));
         }
 
-        let tiles_x = (1..).filter(|i| i * i >= inputs.len()).next().unwrap() as u32;
-        let tiles_y = (inputs.len() as u32 + tiles_x - 1) / tiles_x;
-
-        let box_width = self.width / tiles_x;
-        let box_height = self.height / tiles_x;
-
-        let tiles_off_x = self.width / 2 - tiles_x * box_width / 2;
-        let tiles_off_y = self.height / 2 - tiles_y * box_height / 2;
+        let mut videos = Vec::new();
+        for input in &self.inputs {
+            let video = probe(&input.video_path)?;
+            if let Some(problem) = input.check(&video).into_iter().next() {
+                Err(problem)?;
+            }
+            videos.push(video);
+        }
 
-        let tiles_last_row =
-            if inputs.len() as u32 % tiles_x == 0 {
-                tiles_x
-            } else {
-                inputs.len() as u32 % tiles_x
-            };
-        let tiles_off_x_last = self.width / 2 - tiles_last_row * box_width / 2;
+        // an odd gap gives the extra pixel to the trailing edge of each tile
+        // and to the leading edge of the canvas
+        let lead = self.gap / 2;
+        let trail = self.gap - lead;
+        let mut canvas = Area { x: 0, y: 0, width: self.width, height: self.height };
+        let mut banner = None;
+        if let Some(b) = &self.banner {
+            let (banner_area, rest) = b.split(canvas)?;
+            banner = Some(banner_area);
+            canvas = rest;
+        }
+        let mut panel = None;
+        if let Some(p) = &self.panel {
+            let (panel_area, rest) = p.split(canvas)?;
+            panel = Some(panel_area);
+            canvas = rest;
+        }
+        if 2 * (self.gap + self.border) >= canvas.width.min(canvas.height) {
+            Err("gap and border are too large for the resolution")?;
+        }
+        let canvas = Area {
+            x: canvas.x + trail,
+            y: canvas.y + trail,
+            width: canvas.width - self.gap,
+            height: canvas.height - self.gap
+        };
+        let boxes: Vec<_> = self.layout.boxes(videos.len() as u32, canvas)?.into_iter()
+            .map(|area| Area {
+                x: area.x + lead + self.border,
+                y: area.y + lead + self.border,
+                width: area.width.saturating_sub(self.gap + 2 * self.border).max(1),
+                height: area.height.saturating_sub(self.gap + 2 * self.border).max(1)
+            })
+            .collect();
 
         let pause = (self.pause * self.fps as f64 + 0.5) as u32;
 
-        let mut tiles: Vec<_> = inputs.into_iter().enumerate()
-            .map(|(i, (width, height, first_split, time))| {
-                let (w1, h1) = (box_width, height * box_width / width);
-                let (w2, h2) = (width * box_height / height, box_height);
+        let start_frames: Vec<_> = starts.iter().zip(videos.iter())
+            .map(|(start, video)| (start.seconds(video.frame_rate) * self.fps as f64 + 0.5) as u32)
+            .collect();
+        let start = start_frames.iter().cloned()
+            .fold((self.preroll * self.fps as f64 + 0.5) as u32, u32::min);
+
+        let mut tiles: Vec<_> = videos.iter().enumerate()
+            .map(|(i, video)| {
+                let VideoInfo { width, height, duration, .. } = *video;
+                let (width, height) = match self.inputs[i].crop {
+                    Some(crop) => (crop.width, crop.height),
+                    None => (width, height)
+                };
+                let tile_box = boxes[i];
+                let fit = self.inputs[i].fit.unwrap_or(self.fit);
+
+                let (w1, h1) = (tile_box.width, height * tile_box.width / width);
+                let (w2, h2) = (width * tile_box.height / height, tile_box.height);
                 let (width, height) =
-                    if w1 <= box_width && h1 <= box_height {
+                    if fit != FitMode::Fit {
+                        (tile_box.width, tile_box.height)
+                    } else if w1 <= tile_box.width && h1 <= tile_box.height {
                         (w1, h1)
                     } else {
                         (w2, h2)
                     };
 
-                let tx = i as u32 % tiles_x;
-                let ty = i as u32 / tiles_x;
-
-                let tiles_off_x =
-                    if ty == tiles_y - 1 {
-                        tiles_off_x_last
-                    } else {
-                        tiles_off_x
-                    };
-
-                let offset = first_split as u32 * self.fps;
-                let length = (time * self.fps as f64) as u32 - offset;
+                let offset = start_frames[i] - start;
+                let length = (duration * self.fps as f64) as u32 - offset;
 
                 RenderTileInfo {
                     input: i,
                     offset,
                     length,
-                    x: tiles_off_x + tx * box_width + box_width / 2 - width / 2,
-                    y: tiles_off_y + ty * box_height + box_height / 2 - height / 2,
+                    x: tile_box.x + tile_box.width / 2 - width / 2,
+                    y: tile_box.y + tile_box.height / 2 - height / 2,
                     width,
                     height,
+                    fit,
                     splits: Vec::with_capacity(n_splits)
                 }
             })
             .collect();
+        if let Some(featured) = self.layout.featured() {
+            tiles.sort_by_key(|tile| tile.input != featured);
+        }
 
-        let mut start = 0;
-        let mut length = 0;
+        let mut length = start;
         let mut pauses = Vec::new();
 
         for i in 0..n_splits {
             let mut t_max = 0;
             for tile in tiles.iter_mut() {
                 let input = &self.inputs[tile.input];
+                let splits = &splits[tile.input];
+                let frame_rate = videos[tile.input].frame_rate;
 
                 let t_last =
                     if i == 0 {
-                        0
+                        start
                     } else {
-                        (input.splits[i - 1] * self.fps as f64 + 0.5) as u32 - tile.offset + 1
+                        (splits[i - 1].time.seconds(frame_rate) * self.fps as f64 + 0.5) as u32 - tile.offset
                     };
-                let t_next = (input.splits[i] * self.fps as f64 + 0.5) as u32 - tile.offset + 1;
+                let t_next = (splits[i].time.seconds(frame_rate) * self.fps as f64 + 0.5) as u32 - tile.offset;
                 let t_split = t_next - t_last;
+                if t_next > tile.length {
+                    Err(format!("split {} is beyond the end of video: {:?}", i + 1, input.video_path))?;
+                }
 
                 tile.splits.push((length, length + t_split));
                 t_max = t_max.max(t_split);
             }
 
             length += t_max;
-            if i == 0 {
-                start = length;
-            }
             pauses.push(length);
             length += pause;
         }
 
-        Ok(RenderInfo { start, length, tiles, pauses })
+        let results_length = (self.results * self.fps as f64 + 0.5) as u32;
+        let results = Some(length).filter(|_| results_length > 0);
+        length += results_length;
+
+        Ok(RenderInfo { start, length, tiles, pauses, segments, banner, panel, results })
+    }
+
+    /// Checks all inputs and their videos without rendering and returns all
+    /// problems and warnings found.
+    pub fn check(&self) -> Result<(Vec<String>, Vec<String>), Box<dyn Error>> {
+        find_exec("ffprobe").ok_or("ffprobe not found")?;
+
+        let mut res = Vec::new();
+        let mut warnings = Vec::new();
+        let mut runs = Vec::new();
+        for input in &self.inputs {
+            match probe(&input.video_path) {
+                Ok(video) =>
+                    res.extend(input.check(&video)),
+                Err(err) =>
+                    res.push(err.to_string())
+            }
+            if let Ok((_, splits)) = input.run() {
+                runs.push(splits);
+            }
+        }
+
+        if !runs.is_empty() && runs.len() == self.inputs.len() {
+            match match_splits(&self.inputs, &runs) {
+                Ok((splits, matched)) => {
+                    if splits[0].is_empty() && runs.iter().all(|splits| !splits.is_empty()) {
+                        res.push("inputs have no splits in common".to_string());
+                    }
+                    warnings = matched;
+                },
+                Err(err) =>
+                    res.push(err.to_string())
+            }
+        }
+
+        Ok((res, warnings))
     }
 
     pub fn play(&self, info: &RenderInfo) -> Result<(), Box<dyn Error>> {
@@ -199,7 +490,7 @@ impl Config {
 
         let mut ffplay = Command::new(&ffplay_path)
             .arg("-f").arg("rawvideo")
-            .arg("-pixel_format").arg("rgb24")
+            .arg("-pixel_format").arg(self.pixel_format())
             .arg("-video_size").arg(format!("{}x{}", self.width, self.height))
             .arg("-framerate").arg(format!("{}", self.fps))
             .arg("-window_title").arg("SplitScreen Playback")
@@ -223,6 +514,9 @@ impl Config {
     }
 
     pub fn encode_to_stdout(&self, info: &RenderInfo, encoder: Encoder, report: bool) -> Result<(), Box<dyn Error>> {
+        if encoder == Encoder::PNG {
+            Err("cannot write a PNG sequence to stdout")?;
+        }
         let mut ffmpeg = self.encode_command(encoder, report)?
             .arg("-")
             .stdout(Stdio::inherit())
@@ -261,15 +555,18 @@ impl Config {
 
     fn encode_command(&self, encoder: Encoder, report: bool) -> Result<Command, Box<dyn Error>> {
         let ffmpeg = find_exec("ffmpeg").ok_or("ffmpeg not found")?;
+        if self.has_alpha() && !encoder.alpha() {
+            Err(format!("encoder {} does not support transparency", encoder))?;
+        }
 
         let mut cmd = Command::new(&ffmpeg);
         cmd
             .arg("-f").arg("rawvideo")
-            .arg("-pixel_format").arg("rgb24")
+            .arg("-pixel_format").arg(self.pixel_format())
             .arg("-video_size").arg(format!("{}x{}", self.width, self.height))
             .arg("-framerate").arg(format!("{}", self.fps))
             .arg("-i").arg("-")
-            .arg("-f").arg("mp4");
+            .arg("-f").arg(encoder.format());
 
         encoder.apply_args(&mut cmd);
 
@@ -288,20 +585,29 @@ impl Config {
         self.render_raw(info, File::create(output)?, report)
     }
 
-    pub fn render_raw<W: Write>(&self, info: &RenderInfo, mut output: W, report: bool) -> Result<(), Box<dyn Error>> {
-        self.render(info, |(frame_idx, frame)| {
+    pub fn render_raw<W: Write>(&self, info: &RenderInfo, output: W, report: bool) -> Result<(), Box<dyn Error>> {
+        if self.has_alpha() {
+            self.write_frames::<Rgba<u8>, W>(info, output, report)
+        } else {
+            self.write_frames::<Rgb<u8>, W>(info, output, report)
+        }
+    }
+
+    fn write_frames<P: FramePixel, W: Write>(&self, info: &RenderInfo, mut output: W, report: bool) -> Result<(), Box<dyn Error>> {
+        self.render::<P, _>(info, |(frame_idx, frame)| {
             if report {
+                if let Some(i) = info.tiles[0].splits.iter().position(|(start, _)| *start == frame_idx) {
+                    if let Some(name) = &info.segments[i] {
+                        eprintln!("[splitscreen] segment: {}/{} {}", i + 1, info.segments.len(), name);
+                    }
+                }
                 eprintln!("[splitscreen] progress: {}/{}", frame_idx, info.length);
             }
-            if let Some(frame) = frame {
-                if let Err(err) = output.write_all(frame.as_raw()) {
-                    if err.kind() == io::ErrorKind::BrokenPipe {
-                        Ok(false)
-                    } else {
-                        Err(err)?
-                    }
+            if let Err(err) = output.write_all(frame.as_raw()) {
+                if err.kind() == io::ErrorKind::BrokenPipe {
+                    Ok(false)
                 } else {
-                    Ok(true)
+                    Err(err)?
                 }
             } else {
                 Ok(true)
@@ -309,29 +615,16 @@ impl Config {
         })
     }
 
-    pub fn render<F>(&self, info: &RenderInfo, mut output: F) -> Result<(), Box<dyn Error>>
-        where F: FnMut((u32, Option<&RgbImage>)) -> Result<bool, Box<dyn Error>>
+    pub fn render<P, F>(&self, info: &RenderInfo, mut output: F) -> Result<(), Box<dyn Error>>
+        where P: FramePixel, F: FnMut((u32, &Frame<P>)) -> Result<bool, Box<dyn Error>>
     {
         let ffmpeg = find_exec("ffmpeg").ok_or("ffmpeg not found")?;
-
-        let font_prop = system_fonts::FontPropertyBuilder::new()
-            .monospace().build();
-        let (font_data, _) = system_fonts::get(&font_prop)
-            .ok_or("could not find monospace font")?;
-        let font: Font = Font::try_from_bytes(&font_data)
-            .ok_or("could not find monospace font")?;
+        let font = load_font()?;
+        let canvas = self.draw_canvas(info)?;
 
         let mut ffmpegs: Vec<_> = Vec::new();
-        for tile in info.tiles.iter() {
-            ffmpegs.push(Command::new(&ffmpeg)
-                .arg("-hwaccel").arg("auto")
-                .arg("-ss").arg(format_time(tile.offset as f64 / self.fps as f64))
-                .arg("-i").arg(&self.inputs[tile.input].video_path)
-                .arg("-c:v").arg("rawvideo")
-                .arg("-pix_fmt").arg("rgb24")
-                .arg("-vf").arg(format!("scale={}:{}", tile.width, tile.height))
-                .arg("-r").arg(format!("{}", self.fps))
-                .arg("-f").arg("rawvideo")
+        for tile in info.tiles.iter().filter(|_| !self.overlay) {
+            ffmpegs.push(self.decoder(&ffmpeg, tile, 0, P::FORMAT)
                 .arg("-")
                 .stdin(Stdio::null())
                 .stdout(Stdio::piped())
@@ -345,13 +638,13 @@ impl Config {
                 let mut stdout = ffmpeg.stdout.take().unwrap();
                 let tile = tile.clone();
                 thread::spawn(move || {
-                    let bufsize = tile.width as usize * tile.height as usize * 3;
+                    let bufsize = tile.width as usize * tile.height as usize * P::CHANNEL_COUNT as usize;
                     loop {
                         let mut buf = vec![0u8; bufsize];
                         if stdout.read_exact(&mut buf[..]).is_err() {
                             break;
                         }
-                        let img = RgbImage::from_raw(tile.width, tile.height, buf).unwrap();
+                        let img = Frame::<P>::from_raw(tile.width, tile.height, buf).unwrap();
                         if tx.send(img).is_err() {
                             break;
                         }
@@ -361,124 +654,512 @@ impl Config {
             })
             .collect();
 
-        let mut frame = RgbImage::new(self.width as u32, self.height as u32);
-        let mut frame_cmp_start = None;
+        let mut frame = canvas.clone();
+        let mut images: Vec<Option<Frame<P>>> = vec![None; info.tiles.len()];
 
         for frame_idx in 0..info.length {
-            for (tile, channel) in info.tiles.iter().zip(ffmpegs_channels.iter()) {
-                let (split_idx, (start, end)) = tile.splits.iter().cloned().enumerate()
-                    .filter(|(_, (start, _end))| *start <= frame_idx)
-                    .last().unwrap();
+            for ((tile, channel), image) in info.tiles.iter().zip(ffmpegs_channels.iter()).zip(images.iter_mut()) {
+                let (_, (_, end)) = info.segment_at(tile, frame_idx);
+                if frame_idx < end {
+                    *image = channel.recv().ok();
+                } else if frame_idx == end {
+                    image.iter_mut().for_each(grayscale);
+                }
+            }
 
-                let mut diff = None;
+            self.compose(&mut frame, &canvas, &font, info, &images, frame_idx);
 
-                if frame_idx == start {
-                    frame_cmp_start = None;
-                }
+            if !output((frame_idx, &frame))? {
+                break;
+            }
+        }
 
-                if frame_idx < end {
-                    if let Ok(buf) = channel.recv() {
-                        frame.copy_from(&buf, tile.x, tile.y).unwrap();
+        Ok(())
+    }
+
+    /// Renders only the frame `frame_idx`, decoding a single frame of each
+    /// video.
+    pub fn preview(&self, info: &RenderInfo, frame_idx: u32) -> Result<RgbaImage, Box<dyn Error>> {
+        if frame_idx >= info.length {
+            Err(format!("frame {} is beyond the last frame {}", frame_idx, info.length - 1))?;
+        }
+
+        let ffmpeg = find_exec("ffmpeg").ok_or("ffmpeg not found")?;
+        let font = load_font()?;
+        let canvas = self.draw_canvas(info)?;
+
+        let mut images = Vec::new();
+        for tile in info.tiles.iter() {
+            // same number of frames as `render` takes from the decoder
+            let decoded = (frame_idx + 1).min(info.start) + tile.splits.iter()
+                .map(|(start, end)| (frame_idx + 1).clamp(*start, *end) - start)
+                .sum::<u32>();
+
+            let mut image = None;
+            if decoded > 0 && !self.overlay {
+                let output = self.decoder(&ffmpeg, tile, decoded - 1, Rgba::<u8>::FORMAT)
+                    .arg("-frames:v").arg("1")
+                    .arg("-")
+                    .stdin(Stdio::null())
+                    .stderr(Stdio::null())
+                    .output()?;
+                let mut buf = output.stdout;
+                buf.truncate(tile.width as usize * tile.height as usize * 4);
+                image = RgbaImage::from_raw(tile.width, tile.height, buf);
+            }
+
+            let (_, (_, end)) = info.segment_at(tile, frame_idx);
+            if frame_idx >= end {
+                image.iter_mut().for_each(grayscale);
+            }
+            images.push(image);
+        }
+
+        let mut frame = canvas.clone();
+        self.compose(&mut frame, &canvas, &font, info, &images, frame_idx);
+        Ok(frame)
+    }
+
+    pub fn has_alpha(&self) -> bool {
+        self.alpha || self.overlay
+    }
+
+    /// Pixel format of the rendered frames.
+    pub fn pixel_format(&self) -> &'static str {
+        if self.has_alpha() {
+            Rgba::<u8>::FORMAT
+        } else {
+            Rgb::<u8>::FORMAT
+        }
+    }
+
+    /// Draws the parts of the frames that do not change.
+    fn draw_canvas<P: FramePixel>(&self, info: &RenderInfo) -> Result<Frame<P>, Box<dyn Error>> {
+        let mut canvas = match &self.background {
+            Some(path) => {
+                let background = image::open(path)
+                    .map_err(|e| format!("cannot open {}: {}", path.display(), e))?
+                    .to_rgba8();
+                let background = imageops::resize(&background, self.width, self.height, FilterType::Triangle);
+                Frame::from_fn(self.width, self.height, |x, y| P::from_rgba(*background.get_pixel(x, y)))
+            },
+            None if self.has_alpha() =>
+                Frame::from_pixel(self.width, self.height, P::from_rgba(Rgba([0, 0, 0, 0]))),
+            None =>
+                Frame::from_pixel(self.width, self.height, P::from_rgba(Rgba([0, 0, 0, 255])))
+        };
+        if let Some(area) = info.banner {
+            panel::draw_background(&mut canvas, area);
+        }
+        if let Some(area) = info.panel {
+            panel::draw_background(&mut canvas, area);
+        }
+        if self.border > 0 {
+            for tile in info.tiles.iter() {
+                let color = self.inputs[tile.input].border.unwrap_or(self.border_color);
+                draw_filled_rect_mut(&mut canvas,
+                    Rect::at(tile.x as i32 - self.border as i32, tile.y as i32 - self.border as i32)
+                        .of_size(tile.width + 2 * self.border, tile.height + 2 * self.border),
+                    P::from_rgba(color.rgba())
+                );
+            }
+        }
+        Ok(canvas)
+    }
+
+    /// Returns an ffmpeg command that decodes the video of `tile` as
+    /// rawvideo, starting at frame `first_frame` of the tile. The output file
+    /// has to be added.
+    fn decoder(&self, ffmpeg: &Path, tile: &RenderTileInfo, first_frame: u32, pixel_format: &str) -> Command {
+        let scale = match tile.fit {
+            FitMode::Fill =>
+                format!("scale={w}:{h}:force_original_aspect_ratio=increase,crop={w}:{h}",
+                    w = tile.width, h = tile.height),
+            FitMode::Fit | FitMode::Stretch =>
+                format!("scale={}:{}", tile.width, tile.height)
+        };
+        let filter = match self.inputs[tile.input].crop {
+            Some(crop) => format!("crop={}:{}:{}:{},{}", crop.width, crop.height, crop.x, crop.y, scale),
+            None => scale
+        };
+
+        let mut cmd = Command::new(ffmpeg);
+        cmd
+            .arg("-hwaccel").arg("auto")
+            .arg("-ss").arg(format_time((tile.offset + first_frame) as f64 / self.fps as f64))
+            .arg("-i").arg(&self.inputs[tile.input].video_path)
+            .arg("-c:v").arg("rawvideo")
+            .arg("-pix_fmt").arg(pixel_format)
+            .arg("-vf").arg(filter)
+            .arg("-r").arg(format!("{}", self.fps))
+            .arg("-f").arg("rawvideo");
+        cmd
+    }
+
+    /// Draws frame `frame_idx` from the canvas and the current image of each
+    /// tile.
+    fn compose<P: FramePixel>(&self, frame: &mut Frame<P>, canvas: &Frame<P>, font: &Font, info: &RenderInfo, images: &[Option<Frame<P>>], frame_idx: u32) {
+        if info.results.is_some_and(|results| frame_idx >= results) {
+            draw_results(frame, font, self, info);
+            return;
+        }
+
+        frame.copy_from_slice(canvas);
+
+        // time loss is measured from the first tile that finished the segment
+        let cmp_start = info.tiles.iter()
+            .map(|tile| info.segment_at(tile, frame_idx).1.1)
+            .filter(|end| *end <= frame_idx)
+            .min();
+        let reference = self.reference
+            .and_then(|input| info.tiles.iter().position(|tile| tile.input == input));
+
+        // enlarged leaders are drawn last to be on top, except for the
+        // featured tile of the pip layout, which stays below the insets
+        let leaders: Vec<_> = info.tiles.iter()
+            .map(|tile| self.leader.zip(info.leader_end(tile, frame_idx)))
+            .collect();
+        let pip_featured = match self.layout {
+            Layout::Pip(featured) => Some(featured.featured),
+            _ => None
+        };
+        let mut order: Vec<_> = (0..info.tiles.len()).collect();
+        order.sort_by_key(|i| {
+            leaders[*i].is_some_and(|(leader, _)| leader.enlarge > 1.0)
+                && Some(info.tiles[*i].input) != pip_featured
+        });
+
+        for i in order {
+            let tile = &info.tiles[i];
+            let (split_idx, (start, end)) = info.segment_at(tile, frame_idx);
+            let started = tile.splits[split_idx].0 <= frame_idx;
+            let area = match leaders[i] {
+                Some((leader, leader_end)) =>
+                    leader.area(tile, frame_idx - leader_end, self),
+                None =>
+                    Area { x: tile.x, y: tile.y, width: tile.width, height: tile.height }
+            };
+
+            let delta = match (self.cmp, reference) {
+                // the reference shows its own time instead of a difference
+                (Some(Compare::Cumulative), Some(r)) if r == i && started =>
+                    Some(Delta::Time(tile.total_time(split_idx) - (end - frame_idx.min(end)))),
+                (Some(_), Some(r)) if r == i && started =>
+                    Some(Delta::Time(frame_idx.min(end) - start)),
+                (Some(Compare::Cumulative), _) if started => {
+                    // the difference of the last finished segment
+                    if frame_idx >= end {
+                        Some(Delta::Diff(info.total_diff(i, split_idx, reference)))
                     } else {
-                        draw_filled_rect_mut(&mut frame,
-                            Rect::at(tile.x as i32, tile.y as i32)
-                                .of_size(tile.width, tile.height),
-                            Rgb([ 0, 0, 0 ])
-                        );
+                        split_idx.checked_sub(1)
+                            .map(|last| Delta::Diff(info.total_diff(i, last, reference)))
                     }
-
-                } else {
-                    if frame_idx == end {
-                        for y in tile.y..(tile.y + tile.height) {
-                            for x in tile.x..(tile.x + tile.width) {
-                                let px = &mut frame[(x, y)];
-                                let v = (
-                                    0.2989 * px[0] as f64 +
-                                    0.5870 * px[1] as f64 +
-                                    0.1140 * px[2] as f64
-                                ) as u8;
-                                px[0] = v;
-                                px[1] = v;
-                                px[2] = v;
-                            }
-                        }
+                },
+                (Some(_), Some(r)) if started => {
+                    // grows from whichever of the two finished first
+                    let ref_end = info.tiles[r].splits[split_idx].1;
+                    if frame_idx >= end.min(ref_end) {
+                        Some(Delta::Diff(frame_idx.min(end) as i64 - frame_idx.min(ref_end) as i64))
+                    } else {
+                        None
                     }
+                },
+                (Some(Compare::TimeLoss), None) =>
+                    cmp_start.map(|cmp_start| Delta::Diff((frame_idx.min(end) - cmp_start) as i64)),
+                (Some(Compare::TimeSave), None) if frame_idx >= end =>
+                    Some(Delta::Diff(-((frame_idx.min(info.pauses[split_idx]) - end) as i64))),
+                _ => None
+            };
 
-                    if start != 0 {
-                        match self.cmp {
-                            Some(Compare::TimeLoss) => {
-                                if frame_cmp_start.is_none() {
-                                    frame_cmp_start = Some(frame_idx);
-                                }
-                            },
-                            Some(Compare::TimeSave) => {
-                                diff = Some((true,
-                                    frame_idx.min(info.pauses[split_idx]) - end));
-                            },
-                            _ => {}
-                        }
-                    }
+            if !self.overlay {
+                match &images[i] {
+                    Some(image) if area.width != tile.width || area.height != tile.height => {
+                        let image = imageops::resize(image, area.width, area.height, FilterType::Triangle);
+                        frame.copy_from(&image, area.x, area.y).unwrap();
+                    },
+                    Some(image) =>
+                        frame.copy_from(image, area.x, area.y).unwrap(),
+                    None =>
+                        draw_filled_rect_mut(frame,
+                            Rect::at(area.x as i32, area.y as i32)
+                                .of_size(area.width, area.height),
+                            P::from_rgba(Rgba([0, 0, 0, 255]))
+                        )
+                }
+            }
+
+            // badges go below a name label at the top
+            let mut top = area.y as i32;
+            if let (Some(name), LabelPosition::Top) = (&self.inputs[tile.input].name, self.labels) {
+                let (_, height) = text_box_size(font, Scale::uniform(48.0), name);
+                top += height as i32 / 4 + height as i32;
+            }
+
+            if let Some((leader, _)) = leaders[i] {
+                let color = leader.color.rgba();
+                let width = (area.width.min(area.height) / 60).max(3);
+                let (x, y) = (area.x as i32 - width as i32, area.y as i32 - width as i32);
+                let (outer_width, outer_height) = (area.width + 2 * width, area.height + 2 * width);
+                for rect in [
+                    Rect::at(x, y).of_size(outer_width, width),
+                    Rect::at(x, y + (outer_height - width) as i32).of_size(outer_width, width),
+                    Rect::at(x, y).of_size(width, outer_height),
+                    Rect::at(x + (outer_width - width) as i32, y).of_size(width, outer_height)
+                ] {
+                    draw_filled_rect_mut(frame, rect, P::from_rgba(color));
                 }
 
-                if let Some(cmp_start) = frame_cmp_start {
-                    diff = Some((false, frame_idx.min(end) - cmp_start));
+                if self.placements.is_none() {
+                    let scale = Scale::uniform(48.0);
+                    let (_, height) = text_box_size(font, scale, "1st");
+                    let margin = height as i32 / 4;
+                    draw_text_box(frame, font, scale, color, area.x as i32 + margin, top + margin, "1st");
                 }
+            }
 
-                if let Some((inv, diff)) = diff {
-                    let diff_s = diff as f64 / self.fps as f64;
-                    let (text, color) =
-                        if diff == 0 {
-                            (format_time(diff_s), Rgb([255, 255, 255]))
-                        } else if inv {
-                            (format!("-{}", format_time(diff_s)), Rgb([0, 192, 0]))
-                        } else {
-                            (format!("+{}", format_time(diff_s)), Rgb([192, 0, 0]))
+            if let Some(placements) = self.placements {
+                let finished = tile.splits.get(split_idx)
+                    .is_some_and(|(start, end)| *start <= frame_idx && *end <= frame_idx);
+                if finished {
+                    let mut badges = Vec::new();
+                    if placements != Placements::Total {
+                        let place = info.segment_place(i, split_idx);
+                        badges.push((place, ordinal(place)));
+                    }
+                    if placements != Placements::Segment {
+                        let place = info.total_place(i, split_idx);
+                        badges.push((place, format!("{} overall", ordinal(place))));
+                    }
+
+                    let scale = Scale::uniform(48.0);
+                    let mut y = top;
+                    for (place, text) in badges {
+                        let color = match (place, self.leader) {
+                            (1, Some(leader)) => leader.color.rgba(),
+                            _ => place_color(place)
                         };
-                    let scale = Scale::uniform(64.0);
-
-                    let v_metrics = font.v_metrics(scale);
-                    let offset = point(0.0, v_metrics.ascent);
-
-                    let (mut x_min, mut y_min, mut x_max, mut y_max) =
-                        (i32::MAX, i32::MAX, i32::MIN, i32::MIN);
-                    for next in font.layout(&text, scale, offset) {
-                        if let Some(bbox) = next.pixel_bounding_box() {
-                            x_min = x_min.min(bbox.min.x);
-                            y_min = y_min.min(bbox.min.y);
-                            x_max = x_max.max(bbox.max.x);
-                            y_max = y_max.max(bbox.max.y);
-                        }
+                        let (_, height) = text_box_size(font, scale, &text);
+                        let margin = height as i32 / 4;
+                        draw_text_box(frame, font, scale, color, area.x as i32 + margin, y + margin, &text);
+                        y += margin + height as i32;
                     }
-                    let width = (x_max - x_min) as u32;
-                    let height = (y_max - y_min) as u32;
+                }
+            }
 
-                    let border = height / 2;
+            let mut bottom = (area.y + area.height) as i32;
+
+            if let Some(name) = &self.inputs[tile.input].name {
+                let scale = Scale::uniform(48.0);
+                let (width, height) = text_box_size(font, scale, name);
+                let margin = height as i32 / 4;
+                let x = (area.x + area.width / 2) as i32 - width as i32 / 2;
+                let y = match self.labels {
+                    LabelPosition::Top => area.y as i32 + margin,
+                    LabelPosition::Bottom => bottom - margin - height as i32
+                };
+                if self.labels == LabelPosition::Bottom {
+                    bottom = y;
+                }
+                draw_text_box(frame, font, scale, Rgba([255, 255, 255, 255]), x, y, name);
+            }
 
-                    let x = tile.x + tile.width / 2 - width / 2;
-                    let y = tile.y + tile.height - 2 * border - height;
+            if let Some(timer) = &self.timer {
+                // the timer stops at the end of each segment
+                let (segment, total, running) =
+                    if started {
+                        let segment = frame_idx.min(end) - start;
+                        (segment, tile.total_time(split_idx) - (end - start) + segment, frame_idx < end)
+                    } else {
+                        (0, 0, false)
+                    };
+                let color =
+                    if running {
+                        Rgba([255, 255, 255, 255])
+                    } else {
+                        Rgba([160, 160, 160, 255])
+                    };
 
-                    draw_filled_rect_mut(&mut frame,
-                        Rect::at(x as i32 - border as i32, y as i32 - border as i32)
-                            .of_size(width as u32 + 2 * border, height as u32 + 2 * border),
-                        Rgb([ 0, 0, 0 ])
-                    );
+                // drawn from the bottom up with the total time above the
+                // segment time, which moves the delta above the timer
+                let mut lines = Vec::new();
+                if timer.show != TimerMode::Total {
+                    lines.push((Scale::uniform(32.0), segment));
+                }
+                if timer.show != TimerMode::Segment {
+                    lines.push((Scale::uniform(48.0), total));
+                }
 
-                    draw_text_mut(&mut frame, color, x - x_min as u32, y - y_min as u32, scale, &font, &text);
+                for (scale, time) in lines {
+                    let text = format_time_precision(time as f64 / self.fps as f64, timer.precision);
+                    let (width, height) = text_box_size(font, scale, &text);
+                    let margin = height as i32 / 4;
+                    let x = (area.x + area.width) as i32 - margin - width as i32;
+                    bottom -= margin + height as i32;
+                    draw_text_box(frame, font, scale, color, x, bottom, &text);
                 }
             }
 
-            if frame_idx < info.start {
-                if !output((frame_idx, None))? {
-                    break;
-                }
-            } else {
-                if !output((frame_idx, Some(&frame)))? {
-                    break;
-                }
+            if let Some(delta) = delta {
+                let (text, color) = delta.text(self.fps);
+                let scale = Scale::uniform(64.0);
+
+                let (width, height) = text_box_size(font, scale, &text);
+                let margin = height as i32 / 4;
+                let x = (area.x + area.width / 2) as i32 - width as i32 / 2;
+                let y = bottom - margin - height as i32;
+
+                draw_text_box(frame, font, scale, color, x, y, &text);
             }
         }
 
-        Ok(())
+        if let (Some(banner), Some(area)) = (&self.banner, info.banner) {
+            banner.draw(frame, font, info, area, self.fps, frame_idx);
+        }
+        if let (Some(panel), Some(area)) = (&self.panel, info.panel) {
+            panel.draw(frame, font, self, info, area, frame_idx);
+        }
+    }
+}
+
+impl RenderTileInfo {
+    /// Returns the total time in frames up to and including segment
+    /// `split_idx`.
+    pub fn total_time(&self, split_idx: usize) -> u32 {
+        self.splits[..=split_idx].iter()
+            .map(|(start, end)| end - start)
+            .sum()
+    }
+}
+
+impl Delta {
+    fn text(&self, fps: u32) -> (String, Rgba<u8>) {
+        match *self {
+            Delta::Diff(diff) => {
+                let diff_s = diff.unsigned_abs() as f64 / fps as f64;
+                if diff == 0 {
+                    (format_time(diff_s), Rgba([255, 255, 255, 255]))
+                } else if diff < 0 {
+                    (format!("-{}", format_time(diff_s)), Rgba([0, 192, 0, 255]))
+                } else {
+                    (format!("+{}", format_time(diff_s)), Rgba([192, 0, 0, 255]))
+                }
+            },
+            Delta::Time(time) =>
+                (format_time(time as f64 / fps as f64), Rgba([255, 255, 255, 255]))
+        }
+    }
+}
+
+impl FramePixel for Rgb<u8> {
+    const FORMAT: &'static str = "rgb24";
+
+    fn from_rgba(color: Rgba<u8>) -> Rgb<u8> {
+        let [r, g, b, _] = color.0;
+        Rgb([r, g, b])
+    }
+}
+
+impl FramePixel for Rgba<u8> {
+    const FORMAT: &'static str = "rgba";
+
+    fn from_rgba(color: Rgba<u8>) -> Rgba<u8> {
+        color
+    }
+}
+
+impl Leader {
+    /// Duration of the enlargement in seconds
+    const ANIMATION: f64 = 0.25;
+
+    /// Returns the area of the leading `tile`, `frames` frames after it
+    /// finished the segment.
+    fn area(&self, tile: &RenderTileInfo, frames: u32, config: &Config) -> Area {
+        let progress = (frames as f64 / (Leader::ANIMATION * config.fps as f64)).min(1.0);
+        let scale = 1.0 + (self.enlarge.max(1.0) - 1.0) * progress;
+        let width = ((tile.width as f64 * scale) as u32).min(config.width);
+        let height = ((tile.height as f64 * scale) as u32).min(config.height);
+        let x = (tile.x + tile.width / 2).saturating_sub(width / 2).min(config.width - width);
+        let y = (tile.y + tile.height / 2).saturating_sub(height / 2).min(config.height - height);
+        Area { x, y, width, height }
+    }
+}
+
+impl Default for Leader {
+    fn default() -> Leader {
+        Leader {
+            color: Color([255, 215, 0]),
+            enlarge: 1.0
+        }
+    }
+}
+
+impl Default for Timer {
+    fn default() -> Timer {
+        Timer {
+            show: TimerMode::default(),
+            precision: 2
+        }
+    }
+}
+
+impl RenderInfo {
+    /// Returns the index and timeline range of the segment of `tile` at
+    /// `frame_idx`. During the pre-roll, this is the range of the pre-roll.
+    pub fn segment_at(&self, tile: &RenderTileInfo, frame_idx: u32) -> (usize, (u32, u32)) {
+        tile.splits.iter().cloned().enumerate()
+            .rev()
+            .find(|(_, (start, _end))| *start <= frame_idx)
+            .unwrap_or((0, (0, self.start)))
+    }
+
+    /// Returns the frame at which `tile` finished its segment at `frame_idx`
+    /// if it finished first, or `None` if the segment is not finished yet or
+    /// another tile was faster.
+    pub fn leader_end(&self, tile: &RenderTileInfo, frame_idx: u32) -> Option<u32> {
+        let i = tile.splits.iter().rposition(|(start, _)| *start <= frame_idx)?;
+        let end = tile.splits[i].1;
+        let first = self.tiles.iter().map(|tile| tile.splits[i].1).min()?;
+        Some(end).filter(|end| *end == first && frame_idx >= *end)
+    }
+
+    /// Returns the placement, starting at 1, of tile `tile_idx` in segment
+    /// `split_idx`. Tiles with the same time share a placement.
+    pub fn segment_place(&self, tile_idx: usize, split_idx: usize) -> usize {
+        let time = |tile: &RenderTileInfo| tile.splits[split_idx].1 - tile.splits[split_idx].0;
+        let own = time(&self.tiles[tile_idx]);
+        1 + self.tiles.iter().filter(|tile| time(tile) < own).count()
+    }
+
+    /// Returns the placement, starting at 1, of tile `tile_idx` by its total
+    /// time up to and including segment `split_idx`.
+    pub fn total_place(&self, tile_idx: usize, split_idx: usize) -> usize {
+        let own = self.tiles[tile_idx].total_time(split_idx);
+        1 + self.tiles.iter().filter(|tile| tile.total_time(split_idx) < own).count()
+    }
+
+    /// Returns the difference in frames of the total time of tile
+    /// `tile_idx` up to and including segment `split_idx` to the total time
+    /// of tile `reference`, or to the best total time without a reference.
+    pub fn total_diff(&self, tile_idx: usize, split_idx: usize, reference: Option<usize>) -> i64 {
+        let base = match reference {
+            Some(reference) => self.tiles[reference].total_time(split_idx),
+            None => self.tiles.iter()
+                .map(|tile| tile.total_time(split_idx))
+                .min().unwrap()
+        };
+        self.tiles[tile_idx].total_time(split_idx) as i64 - base as i64
+    }
+
+    /// Returns the frame showing the result of segment `split`, starting at
+    /// 1, which is the first frame of the pause after it or, without pauses,
+    /// its last frame.
+    pub fn split_frame(&self, split: usize) -> Option<u32> {
+        let pause = *self.pauses.get(split.checked_sub(1)?)?;
+        let next = self.tiles[0].splits.get(split)
+            .map(|(start, _)| *start)
+            .or(self.results)
+            .unwrap_or(self.length);
+        Some(pause.min(next - 1))
     }
 }
 
@@ -491,7 +1172,10 @@ impl Encoder {
             Encoder::VAAPI,
             Encoder::NVENC,
             Encoder::AMF,
-            Encoder::QSV
+            Encoder::QSV,
+            Encoder::ProRes,
+            Encoder::PNG,
+            Encoder::QTRLE
         ]
     }
 
@@ -506,10 +1190,33 @@ impl Encoder {
             Encoder::AMF =>
                 "AMD GPU encoder",
             Encoder::QSV =>
-                "Intel GPU encoder"
+                "Intel GPU encoder",
+            Encoder::ProRes =>
+                "ProRes 4444 with alpha (mov)",
+            Encoder::PNG =>
+                "PNG sequence with alpha (output name with a pattern like %05d)",
+            Encoder::QTRLE =>
+                "QuickTime Animation with alpha (mov)"
+        }
+    }
+
+    /// Container format written by ffmpeg.
+    pub fn format(&self) -> &'static str {
+        match self {
+            Encoder::X264 | Encoder::VAAPI | Encoder::NVENC | Encoder::AMF | Encoder::QSV =>
+                "mp4",
+            Encoder::ProRes | Encoder::QTRLE =>
+                "mov",
+            Encoder::PNG =>
+                "image2"
         }
     }
 
+    /// Whether the encoder keeps the alpha channel.
+    pub fn alpha(&self) -> bool {
+        matches!(self, Encoder::ProRes | Encoder::PNG | Encoder::QTRLE)
+    }
+
     pub fn apply_args(&self, cmd: &mut Command) {
         match self {
             Encoder::X264 => {
@@ -534,17 +1241,27 @@ impl Encoder {
             },
             Encoder::QSV => {
                 unimplemented!() //TODO
+            },
+            Encoder::ProRes => {
+                cmd
+                    .arg("-c:v").arg("prores_ks")
+                    .arg("-profile:v").arg("4444")
+                    .arg("-pix_fmt").arg("yuva444p10le");
+            },
+            Encoder::PNG => {
+                cmd
+                    .arg("-c:v").arg("png")
+                    .arg("-pix_fmt").arg("rgba");
+            },
+            Encoder::QTRLE => {
+                cmd
+                    .arg("-c:v").arg("qtrle")
+                    .arg("-pix_fmt").arg("argb");
             }
         }
     }
 }
 
-impl Default for Encoder {
-    fn default() -> Encoder {
-        Encoder::X264
-    }
-}
-
 impl fmt::Display for Encoder {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -557,7 +1274,13 @@ impl fmt::Display for Encoder {
             Encoder::AMF =>
                 write!(f, "amf"),
             Encoder::QSV =>
-                write!(f, "qsv")
+                write!(f, "qsv"),
+            Encoder::ProRes =>
+                write!(f, "prores"),
+            Encoder::PNG =>
+                write!(f, "png"),
+            Encoder::QTRLE =>
+                write!(f, "qtrle")
         }
     }
 }
@@ -566,39 +1289,566 @@ impl Input {
     pub fn new(video_path: &Path) -> Input {
         Input {
             video_path: PathBuf::from(video_path),
-            splits: vec![]
+            name: None,
+            start: None,
+            end: None,
+            crop: None,
+            fit: None,
+            border: None,
+            splits: vec![],
+            source: Source::default()
         }
     }
 
+    /// Reads a split file, which is either a LiveSplit file, a splits.io
+    /// JSON file or a list of arguments, one per line. The format is
+    /// detected by the file extension or else by the first character.
     pub fn from_file(video_path: &Path, path: &Path) -> Result<Input, Box<dyn Error>> {
-        let file = File::open(path)
-            .map_err(|e| format!("cannot open {}: {}", video_path.display(), e))?;
-        let reader = BufReader::new(file);
-        let lines: Vec<_> = reader.lines()
-            .collect::<Result<_, _>>()?;
-        Self::from_args(video_path, lines.into_iter())
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("cannot open {}: {}", path.display(), e))?;
+
+        let ext = path.extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase());
+        let first = text.trim_start().chars().next();
+
+        let mut res = Input::new(video_path);
+        res.source.path = Some(PathBuf::from(path));
+        if ext.as_deref() == Some("lss") || first == Some('<') {
+            res.add_livesplit(path, Attempt::PersonalBest, TimingMethod::RealTime, 0.0)?;
+        } else if ext.as_deref() == Some("json") || first == Some('{') {
+            res.add_splitsio(path, TimingMethod::RealTime, 0.0)?;
+        } else {
+            res.parse_args(text.lines())?;
+        }
+        Ok(res)
     }
 
     pub fn from_args<I, S>(video_path: &Path, lines: I) -> Result<Input, Box<dyn Error>>
         where I: Iterator<Item = S>, S: AsRef<str>
     {
         let mut res = Input::new(video_path);
-        for next in lines {
-            let args: Vec<_> = next.as_ref().split(' ').collect();
-            match args[0] {
-                "split" => {
-                    let time_str = args.get(1).ok_or("missing split time")?;
-                    res.splits.push(parse_split_time(time_str)?);
-                },
-                s =>
-                    eprintln!("warning: unknown field `{}`", s)
+        res.parse_args(lines)?;
+        Ok(res)
+    }
+
+    /// Parses all arguments and fails with `Problems` listing every invalid
+    /// one.
+    fn parse_args<I, S>(&mut self, lines: I) -> Result<(), Box<dyn Error>>
+        where I: Iterator<Item = S>, S: AsRef<str>
+    {
+        let mut problems = Vec::new();
+        for (i, next) in lines.enumerate() {
+            if let Err(e) = self.parse_arg(next.as_ref(), i + 1) {
+                problems.push(format!("{}: {}", self.location(Some(i + 1)), e));
             }
         }
-        Ok(res)
+        if !problems.is_empty() {
+            Err(Problems(problems))?;
+        }
+        Ok(())
+    }
+
+    /// Resolves a path relative to the directory of the split file, if any.
+    fn relative_path(&self, path: &str) -> PathBuf {
+        let dir = self.source.path.as_ref()
+            .and_then(|source| source.parent())
+            .unwrap_or_else(|| Path::new(""));
+        dir.join(path)
+    }
+
+    fn parse_arg(&mut self, arg: &str, line: usize) -> Result<(), Box<dyn Error>> {
+        let args: Vec<_> = arg.split(' ').collect();
+        match args[0] {
+            "split" => {
+                let time_str = args.get(1).ok_or("missing split time")?;
+                let name = args[2..].join(" ");
+                self.splits.push(Split {
+                    time: time_str.parse()?,
+                    name: Some(name.trim().to_string()).filter(|s| !s.is_empty()),
+                    line: Some(line)
+                });
+            },
+            "name" => {
+                let name = args[1..].join(" ");
+                self.name = Some(name.trim().to_string()).filter(|s| !s.is_empty());
+            },
+            "start" => {
+                let time_str = args.get(1).ok_or("missing start time")?;
+                self.start = Some(time_str.parse()?);
+                self.source.start_line = Some(line);
+            },
+            "end" => {
+                let time_str = args.get(1).ok_or("missing end time")?;
+                self.end = Some(time_str.parse()?);
+                self.source.end_line = Some(line);
+            },
+            "crop" => {
+                let mut values = args[1..].iter()
+                    .filter(|s| !s.is_empty())
+                    .map(|s| s.parse().map_err(|_| format!("invalid number: {}", s)));
+                let mut next = || values.next().unwrap_or_else(|| Err("crop needs x, y, width and height".to_string()));
+                self.crop = Some(Area { x: next()?, y: next()?, width: next()?, height: next()? });
+                self.source.crop_line = Some(line);
+            },
+            "fit" => {
+                let fit = args.get(1).ok_or("missing fit mode")?;
+                self.fit = Some(fit.parse()?);
+            },
+            "border" => {
+                let color = args.get(1).ok_or("missing border color")?;
+                self.border = Some(color.parse()?);
+            },
+            "splitsio" => {
+                let path = args.get(1).ok_or("missing splits.io file")?;
+                let timing = match args.get(2) {
+                    Some(s) => s.parse()?,
+                    None => TimingMethod::RealTime
+                };
+                let offset = match args.get(3) {
+                    Some(s) => parse_split_time(s)?,
+                    None => 0.0
+                };
+                self.add_splitsio(&self.relative_path(path), timing, offset)?;
+            },
+            "livesplit" => {
+                let path = args.get(1).ok_or("missing LiveSplit file")?;
+                let attempt = match args.get(2) {
+                    Some(s) => s.parse()?,
+                    None => Attempt::PersonalBest
+                };
+                let timing = match args.get(3) {
+                    Some(s) => s.parse()?,
+                    None => TimingMethod::RealTime
+                };
+                let offset = match args.get(4) {
+                    Some(s) => parse_split_time(s)?,
+                    None => 0.0
+                };
+                self.add_livesplit(&self.relative_path(path), attempt, timing, offset)?;
+            },
+            s =>
+                eprintln!("warning: {}: unknown field `{}`", self.location(Some(line)), s)
+        }
+        Ok(())
+    }
+
+    /// Describes where the given line of this input was read from.
+    pub fn location(&self, line: Option<usize>) -> String {
+        match (&self.source.path, line) {
+            (Some(path), Some(line)) =>
+                format!("{}:{}", path.display(), line),
+            (Some(path), None) =>
+                format!("{}", path.display()),
+            (None, Some(line)) =>
+                format!("{}: argument {}", self.video_path.display(), line),
+            (None, None) =>
+                format!("{}", self.video_path.display())
+        }
+    }
+
+    /// Checks the splits of this input against its video and returns all
+    /// problems found.
+    pub fn check(&self, video: &VideoInfo) -> Vec<String> {
+        let mut res = Vec::new();
+
+        let mut times = Vec::new();
+        let mut splits = self.splits.iter();
+        if let Some(start) = self.start {
+            times.push((start, self.source.start_line, "start of run".to_string()));
+        } else if let Some(split) = splits.next() {
+            times.push((split.time, split.line, "start of run".to_string()));
+        } else {
+            res.push(format!("{}: missing start of run", self.location(None)));
+        }
+        for split in splits {
+            let desc = match &split.name {
+                Some(name) => format!("split `{}`", name),
+                None => "split".to_string()
+            };
+            times.push((split.time, split.line, desc));
+        }
+        if let Some(end) = self.end {
+            times.push((end, self.source.end_line, "end of run".to_string()));
+        }
+        if times.len() == 1 {
+            res.push(format!("{}: missing splits after the start of run", self.location(None)));
+        }
+
+        let mut last: Option<(f64, &str)> = None;
+        for (time, line, desc) in times.iter() {
+            let t = time.seconds(video.frame_rate);
+            if let Some((t_last, desc_last)) = last {
+                if t < t_last {
+                    res.push(format!("{}: {} at {} is before {} at {}",
+                        self.location(*line), desc, format_time(t), desc_last, format_time(t_last)));
+                }
+            }
+            if t > video.duration {
+                res.push(format!("{}: {} at {} is beyond the end of the video at {}",
+                    self.location(*line), desc, format_time(t), format_time(video.duration)));
+            }
+            last = Some((t, desc));
+        }
+
+        if let Some(crop) = self.crop {
+            if crop.width == 0 || crop.height == 0
+                || crop.x + crop.width > video.width || crop.y + crop.height > video.height {
+                res.push(format!("{}: crop region {}x{}+{}+{} is outside of the {}x{} video",
+                    self.location(self.source.crop_line), crop.width, crop.height, crop.x, crop.y,
+                    video.width, video.height));
+            }
+        }
+
+        res
+    }
+
+    /// Appends the splits of a LiveSplit run, where `offset` is the time in
+    /// the video at which the run starts.
+    pub fn add_livesplit(&mut self, path: &Path, attempt: Attempt, timing: TimingMethod, offset: f64) -> Result<(), Box<dyn Error>> {
+        let splits = livesplit::read_splits(path, attempt, timing)?;
+        self.add_run(splits, offset);
+        Ok(())
+    }
+
+    /// Appends the splits of the personal best in a splits.io file, where
+    /// `offset` is the time in the video at which the run starts.
+    pub fn add_splitsio(&mut self, path: &Path, timing: TimingMethod, offset: f64) -> Result<(), Box<dyn Error>> {
+        let splits = splitsio::read_splits(path, timing)?;
+        self.add_run(splits, offset);
+        Ok(())
+    }
+
+    fn add_run(&mut self, splits: Vec<RunSplit>, offset: f64) {
+        self.start = Some(SplitTime::Seconds(offset));
+        self.splits.extend(splits.into_iter()
+            .map(|split| Split {
+                time: SplitTime::Seconds(offset + split.time),
+                name: split.name,
+                line: None
+            }));
+    }
+
+    /// Returns the start of the run and the splits that end each segment.
+    ///
+    /// Without a `start` directive, the first split marks the start of the
+    /// run. An `end` directive adds a final split named "End", so that it
+    /// can be matched by name with the other inputs.
+    pub fn run(&self) -> Result<(SplitTime, Vec<Split>), Box<dyn Error>> {
+        let mut splits = self.splits.clone();
+        let start =
+            if let Some(start) = self.start {
+                start
+            } else if !splits.is_empty() {
+                splits.remove(0).time
+            } else {
+                Err(format!("{}: missing start of run", self.location(None)))?
+            };
+        if let Some(end) = self.end {
+            splits.push(Split { time: end, name: Some("End".to_string()), line: self.source.end_line });
+        }
+        Ok((start, splits))
+    }
+}
+
+
+
+impl SplitTime {
+    /// Converts the split time to seconds, where `frame_rate` is the frame
+    /// rate of the video as a fraction.
+    pub fn seconds(&self, frame_rate: (u32, u32)) -> f64 {
+        match *self {
+            SplitTime::Seconds(s) =>
+                s,
+            SplitTime::Frame(frame) =>
+                frame as f64 * frame_rate.1 as f64 / frame_rate.0 as f64
+        }
+    }
+}
+
+impl FromStr for SplitTime {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<SplitTime, Box<dyn Error>> {
+        let frame = s.strip_prefix('#')
+            .or_else(|| s.strip_suffix('f'));
+        if let Some(frame) = frame {
+            let frame = frame.parse()
+                .map_err(|_| format!("invalid frame number: {}", s))?;
+            Ok(SplitTime::Frame(frame))
+        } else {
+            Ok(SplitTime::Seconds(parse_split_time(s)?))
+        }
+    }
+}
+
+
+impl fmt::Display for Problems {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.join("\n"))
+    }
+}
+
+impl Error for Problems {}
+
+impl fmt::Display for SplitTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SplitTime::Seconds(s) =>
+                write!(f, "{}", format_time(*s)),
+            SplitTime::Frame(frame) =>
+                write!(f, "#{}", frame)
+        }
+    }
+}
+
+impl FromStr for Placements {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Placements, String> {
+        match s {
+            "segment" => Ok(Placements::Segment),
+            "total" => Ok(Placements::Total),
+            "both" => Ok(Placements::Both),
+            _ => Err(format!("invalid placements: {}", s))
+        }
+    }
+}
+
+impl FromStr for TimerMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<TimerMode, String> {
+        match s {
+            "segment" => Ok(TimerMode::Segment),
+            "total" => Ok(TimerMode::Total),
+            "both" => Ok(TimerMode::Both),
+            _ => Err(format!("invalid timer: {}", s))
+        }
+    }
+}
+
+impl FromStr for FitMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<FitMode, String> {
+        match s {
+            "fit" => Ok(FitMode::Fit),
+            "fill" => Ok(FitMode::Fill),
+            "stretch" => Ok(FitMode::Stretch),
+            _ => Err(format!("invalid fit mode: {}", s))
+        }
+    }
+}
+
+impl Default for Color {
+    fn default() -> Color {
+        Color([255, 255, 255])
+    }
+}
+
+impl Color {
+    pub fn rgba(&self) -> Rgba<u8> {
+        let [r, g, b] = self.0;
+        Rgba([r, g, b, 255])
+    }
+}
+
+impl FromStr for Color {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Color, Box<dyn Error>> {
+        let invalid = || format!("invalid color: {}", s);
+        let rgb = match s.to_ascii_lowercase().as_str() {
+            "black" => [0, 0, 0],
+            "white" => [255, 255, 255],
+            "gray" | "grey" => [128, 128, 128],
+            "red" => [192, 0, 0],
+            "green" => [0, 192, 0],
+            "blue" => [0, 0, 192],
+            "yellow" => [255, 192, 0],
+            "gold" => [255, 215, 0],
+            hex => {
+                let hex = hex.strip_prefix('#').unwrap_or(hex);
+                if hex.len() != 6 || !hex.is_ascii() {
+                    Err(invalid())?;
+                }
+                let mut rgb = [0; 3];
+                for (i, v) in rgb.iter_mut().enumerate() {
+                    *v = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16)
+                        .map_err(|_| invalid())?;
+                }
+                rgb
+            }
+        };
+        Ok(Color(rgb))
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [r, g, b] = self.0;
+        write!(f, "#{:02x}{:02x}{:02x}", r, g, b)
+    }
+}
+
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        String::deserialize(deserializer)?
+            .parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for SplitTime {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // seconds are stored as numbers to keep their full precision
+        match self {
+            SplitTime::Seconds(s) =>
+                serializer.serialize_f64(*s),
+            SplitTime::Frame(_) =>
+                serializer.collect_str(self)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for SplitTime {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<SplitTime, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Seconds(f64),
+            Str(String)
+        }
+
+        match Repr::deserialize(deserializer)? {
+            Repr::Seconds(s) =>
+                Ok(SplitTime::Seconds(s)),
+            Repr::Str(s) =>
+                s.parse().map_err(serde::de::Error::custom)
+        }
+    }
+}
+
+
+
+pub fn probe(video_path: &Path) -> Result<VideoInfo, Box<dyn Error>> {
+    let ffprobe = find_exec("ffprobe").ok_or("ffprobe not found")?;
+
+    let mut ffprobe = Command::new(&ffprobe)
+        .arg("-select_streams").arg("v:0")
+        .arg("-show_entries").arg("stream=width,height,r_frame_rate,avg_frame_rate,duration:format=duration")
+        .arg("-of").arg("default=nw=1")
+        .arg(video_path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    let lines: Vec<_> = BufReader::new(ffprobe.stdout.take().unwrap())
+        .lines().collect::<Result<_, _>>()?;
+    ffprobe.wait()?;
+
+    let value = |key: &str| lines.iter()
+        .filter_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+        .find(|value| *value != "N/A" && *value != "0/0");
+    let invalid = || format!("invalid video file: {:?}", video_path);
+
+    let width = value("width").and_then(|s| s.parse().ok()).ok_or_else(invalid)?;
+    let height = value("height").and_then(|s| s.parse().ok()).ok_or_else(invalid)?;
+    let duration = value("duration").and_then(|s| s.parse().ok()).ok_or_else(invalid)?;
+    // r_frame_rate is often bogus for recordings with a variable frame rate
+    let frame_rate: (u32, u32) = value("avg_frame_rate")
+        .or_else(|| value("r_frame_rate"))
+        .and_then(|s| {
+            let (num, den) = s.split_once('/')?;
+            Some((num.parse().ok()?, den.parse().ok()?))
+        })
+        .ok_or_else(invalid)?;
+    let fps = frame_rate.0 as f64 / frame_rate.1 as f64;
+    if !(MIN_FRAME_RATE..=MAX_FRAME_RATE).contains(&fps) {
+        Err(format!("unsupported frame rate {}/{} of video file: {:?}", frame_rate.0, frame_rate.1, video_path))?;
     }
+
+    Ok(VideoInfo { width, height, frame_rate, duration })
 }
 
+/// Splits of every input that are compared with each other
+type MatchedSplits<'a> = Vec<Vec<&'a Split>>;
+
+/// Returns the splits of every input that are used for the comparison and
+/// warnings about splits that are skipped or named differently.
+///
+/// If all inputs have the same number of splits, they are used as-is.
+/// Otherwise, if every split is named, only the splits whose names occur in
+/// all inputs are kept, in the order of the first input. Dropping a split
+/// merges its segment into the following one.
+fn match_splits<'a>(inputs: &[Input], runs: &'a [Vec<Split>]) -> Result<(MatchedSplits<'a>, Vec<String>), Box<dyn Error>> {
+    let n_splits = runs[0].len();
+    if runs.iter().all(|splits| splits.len() == n_splits) {
+        let res: Vec<Vec<_>> = runs.iter()
+            .map(|splits| splits.iter().collect())
+            .collect();
+        let mut warnings = Vec::new();
+        for i in 0..n_splits {
+            let names: Vec<_> = res.iter()
+                .filter_map(|splits| splits[i].name.as_deref())
+                .collect();
+            if names.iter().any(|name| *name != names[0]) {
+                warnings.push(format!("split {} has different names: {}", i + 1, names.join(", ")));
+            }
+        }
+        return Ok((res, warnings));
+    }
+
+    let named = runs.iter()
+        .all(|splits| splits.iter().all(|split| split.name.is_some()));
+    if !named {
+        let counts: Vec<_> = inputs.iter().zip(runs.iter())
+            .map(|(input, splits)| format!("{} has {}", input.location(None), splits.len()))
+            .collect();
+        Err(format!("inputs must have equal number of splits or named splits: {}", counts.join(", ")))?;
+    }
+
+    let mut res: Vec<Vec<&Split>> = vec![Vec::new(); runs.len()];
+    let mut warnings = Vec::new();
+    let mut pos = vec![0; runs.len()];
 
+    for split in runs[0].iter() {
+        let found: Option<Vec<_>> = runs.iter().zip(pos.iter())
+            .map(|(splits, &pos)| {
+                splits.iter().enumerate().skip(pos)
+                    .find(|(_, next)| next.name == split.name)
+                    .map(|(i, _)| i)
+            })
+            .collect();
+
+        if let Some(found) = found {
+            for (i, idx) in found.into_iter().enumerate() {
+                for skipped in &runs[i][pos[i]..idx] {
+                    warnings.push(format!("{}: skipping segment `{}`, which is not present in all inputs",
+                        inputs[i].location(None), skipped.name.as_deref().unwrap()));
+                }
+                res[i].push(&runs[i][idx]);
+                pos[i] = idx + 1;
+            }
+        }
+    }
+
+    for ((input, splits), pos) in inputs.iter().zip(runs.iter()).zip(pos.iter()) {
+        if *pos > 0 && *pos < splits.len() {
+            warnings.push(format!("{}: ignoring splits after `{}`",
+                input.location(None), splits[pos - 1].name.as_deref().unwrap()));
+        }
+    }
+
+    Ok((res, warnings))
+}
 
 pub fn parse_split_time(time_str: &str) -> Result<f64, Box<dyn Error>> {
     let split = time_str.split(':').collect::<Vec<_>>();
@@ -610,13 +1860,14 @@ pub fn parse_split_time(time_str: &str) -> Result<f64, Box<dyn Error>> {
         } else if split.len() == 3 {
             (split[0], split[1], split[2])
         } else {
-            panic!()
+            Err(format!("invalid time: {}", time_str))?
         };
 
-    let h: usize = h_str.parse()?;
-    let m: usize = m_str.parse()?;
-    let s: f64 = s_str.parse()?;
-    if m > 60 || s < 0.0 || s > 60.0 {
+    let invalid = || format!("invalid time: {}", time_str);
+    let h: usize = h_str.parse().map_err(|_| invalid())?;
+    let m: usize = m_str.parse().map_err(|_| invalid())?;
+    let s: f64 = s_str.parse().map_err(|_| invalid())?;
+    if m > 60 || !(0.0..=60.0).contains(&s) {
         Err(format!("invalid time: {}", time_str))?;
     }
 
@@ -624,29 +1875,136 @@ pub fn parse_split_time(time_str: &str) -> Result<f64, Box<dyn Error>> {
 }
 
 pub fn format_time(time: f64) -> String {
+    format_time_precision(time, 3)
+}
+
+/// Formats `time` like `format_time` with `precision` decimal places.
+pub fn format_time_precision(time: f64, precision: u32) -> String {
     if time < 0.0 {
-        return format!("-{}", format_time(-time));
+        return format!("-{}", format_time_precision(-time, precision));
     }
 
-    let ms_total = (time * 1000.0) as u64;
-    let ms = ms_total % 1000;
-    let s_total = ms_total / 1000;
+    // truncated like a speedrun timer, allowing for floating point errors
+    // of times that are exactly on a boundary
+    let unit = 10u64.pow(precision);
+    let frac_total = (time * unit as f64 + 1e-6).floor() as u64;
+    let frac =
+        if precision > 0 {
+            format!(".{:0>width$}", frac_total % unit, width = precision as usize)
+        } else {
+            String::new()
+        };
+    let s_total = frac_total / unit;
     if s_total < 60 {
-        return format!("{:0>2}.{:0>3}", s_total, ms);
+        return format!("{:0>2}{}", s_total, frac);
     }
 
     let s = s_total % 60;
     let m_total = s_total / 60;
     if m_total < 60 {
-        return format!("{:0>2}:{:0>2}.{:0>3}", m_total, s, ms);
+        return format!("{:0>2}:{:0>2}{}", m_total, s, frac);
     }
 
     let m = m_total % 60;
     let h_total = m_total / 60;
-    format!("{:0>2}:{:0>2}:{:0>2}.{:0>3}", h_total, m, s, ms)
+    format!("{:0>2}:{:0>2}:{:0>2}{}", h_total, m, s, frac)
+}
+
+/// Formats a placement like `1st`, `2nd` or `11th`.
+pub fn ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th"
+    };
+    format!("{}{}", n, suffix)
+}
+
+/// Color of a placement: gold, silver and bronze for the first three and
+/// white for the rest.
+pub fn place_color(place: usize) -> Rgba<u8> {
+    match place {
+        1 => Rgba([255, 215, 0, 255]),
+        2 => Rgba([192, 192, 192, 255]),
+        3 => Rgba([205, 127, 50, 255]),
+        _ => Rgba([255, 255, 255, 255])
+    }
+}
+
+/// Returns the offset and size of the pixel bounding box of `text`.
+pub fn text_bounds(font: &Font, scale: Scale, text: &str) -> (i32, i32, u32, u32) {
+    let v_metrics = font.v_metrics(scale);
+    let offset = point(0.0, v_metrics.ascent);
+
+    let (mut x_min, mut y_min, mut x_max, mut y_max) =
+        (i32::MAX, i32::MAX, i32::MIN, i32::MIN);
+    for next in font.layout(text, scale, offset) {
+        if let Some(bbox) = next.pixel_bounding_box() {
+            x_min = x_min.min(bbox.min.x);
+            y_min = y_min.min(bbox.min.y);
+            x_max = x_max.max(bbox.max.x);
+            y_max = y_max.max(bbox.max.y);
+        }
+    }
+
+    if x_min > x_max {
+        (0, 0, 0, 0)
+    } else {
+        (x_min, y_min, (x_max - x_min) as u32, (y_max - y_min) as u32)
+    }
+}
+
+/// Returns the size of the box drawn by `draw_text_box`.
+fn text_box_size(font: &Font, scale: Scale, text: &str) -> (u32, u32) {
+    let (_, _, width, height) = text_bounds(font, scale, text);
+    let border = height / 2;
+    (width + 2 * border, height + 2 * border)
+}
+
+/// Draws `text` on a black box with its top left corner at (`x`, `y`). The
+/// box is padded by half the text height.
+fn draw_text_box<P: FramePixel>(frame: &mut Frame<P>, font: &Font, scale: Scale, color: Rgba<u8>, x: i32, y: i32, text: &str) {
+    let (x_min, y_min, width, height) = text_bounds(font, scale, text);
+    let border = height / 2;
+
+    draw_filled_rect_mut(frame,
+        Rect::at(x, y).of_size(width + 2 * border, height + 2 * border),
+        P::from_rgba(Rgba([0, 0, 0, 255]))
+    );
+
+    let x = x + border as i32 - x_min;
+    let y = y + border as i32 - y_min;
+    draw_text_mut(frame, P::from_rgba(color), x.max(0) as u32, y.max(0) as u32, scale, font, text);
+}
+
+fn load_font() -> Result<Font<'static>, Box<dyn Error>> {
+    let font_prop = system_fonts::FontPropertyBuilder::new()
+        .monospace().build();
+    let (font_data, _) = system_fonts::get(&font_prop)
+        .ok_or("could not find monospace font")?;
+    let font = Font::try_from_vec(font_data)
+        .ok_or("could not find monospace font")?;
+    Ok(font)
+}
+
+/// Converts `image` to grayscale.
+fn grayscale<P: FramePixel>(image: &mut Frame<P>) {
+    for px in image.pixels_mut() {
+        let px = px.channels_mut();
+        let v = (
+            0.2989 * px[0] as f64 +
+            0.5870 * px[1] as f64 +
+            0.1140 * px[2] as f64
+        ) as u8;
+        px[0] = v;
+        px[1] = v;
+        px[2] = v;
+    }
 }
 
-fn find_exec(name: &str) -> Option<PathBuf> {
+pub fn find_exec(name: &str) -> Option<PathBuf> {
     let mut paths = Vec::new();
     let name_exe = name.to_string() + ".exe";
 
@@ -673,5 +2031,197 @@ fn find_exec(name: &str) -> Option<PathBuf> {
         }
     }
 
-    return None;
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(names: &[Option<&str>]) -> Vec<Split> {
+        names.iter().enumerate()
+            .map(|(i, name)| Split {
+                time: SplitTime::Seconds(i as f64 + 1.0),
+                name: name.map(String::from),
+                line: None
+            })
+            .collect()
+    }
+
+    fn matched(inputs: &[Input], runs: &[Vec<Split>]) -> (Vec<Vec<Option<String>>>, Vec<String>) {
+        let (splits, warnings) = match_splits(inputs, runs).unwrap();
+        let names = splits.iter()
+            .map(|splits| splits.iter().map(|split| split.name.clone()).collect())
+            .collect();
+        (names, warnings)
+    }
+
+    #[test]
+    fn match_unnamed_splits() {
+        let inputs = [Input::new(Path::new("a.mp4")), Input::new(Path::new("b.mp4"))];
+        let runs = [run(&[None, None]), run(&[None, Some("Water")])];
+        let (names, warnings) = matched(&inputs, &runs);
+        assert_eq!(names[1], vec![None, Some("Water".to_string())]);
+        assert!(warnings.is_empty());
+
+        let runs = [run(&[None, None]), run(&[None])];
+        assert!(match_splits(&inputs, &runs).is_err());
+    }
+
+    #[test]
+    fn match_differently_named_splits() {
+        let inputs = [Input::new(Path::new("a.mp4")), Input::new(Path::new("b.mp4"))];
+        let runs = [run(&[Some("Forest"), Some("Water")]), run(&[Some("Forest"), Some("Fire")])];
+        let (_, warnings) = matched(&inputs, &runs);
+        assert_eq!(warnings, vec!["split 2 has different names: Water, Fire"]);
+    }
+
+    #[test]
+    fn match_named_splits() {
+        let inputs = [Input::new(Path::new("a.mp4")), Input::new(Path::new("b.mp4"))];
+        let runs = [
+            run(&[Some("Forest"), Some("Extra"), Some("Water"), Some("End")]),
+            run(&[Some("Forest"), Some("Fire"), Some("Water"), Some("End"), Some("Credits")])
+        ];
+        let (names, warnings) = matched(&inputs, &runs);
+        let expected: Vec<_> = ["Forest", "Water", "End"].iter()
+            .map(|name| Some(name.to_string()))
+            .collect();
+        assert_eq!(names, vec![expected.clone(), expected]);
+        assert_eq!(warnings, vec![
+            "a.mp4: skipping segment `Extra`, which is not present in all inputs",
+            "b.mp4: skipping segment `Fire`, which is not present in all inputs",
+            "b.mp4: ignoring splits after `End`"
+        ]);
+    }
+
+    #[test]
+    fn parse_split_times() {
+        let parse = |s: &str| s.parse::<SplitTime>().unwrap();
+        assert_eq!(parse("#150"), SplitTime::Frame(150));
+        assert_eq!(parse("720f"), SplitTime::Frame(720));
+        assert_eq!(parse("23.456"), SplitTime::Seconds(23.456));
+        assert_eq!(parse("1:23.5"), SplitTime::Seconds(83.5));
+        assert_eq!(parse("1:02:03"), SplitTime::Seconds(3723.0));
+        assert!("#1.5".parse::<SplitTime>().is_err());
+        assert!("#".parse::<SplitTime>().is_err());
+        assert!("1:61".parse::<SplitTime>().is_err());
+    }
+
+    #[test]
+    fn split_time_seconds() {
+        assert_eq!(SplitTime::Frame(150).seconds((30, 1)), 5.0);
+        assert_eq!(SplitTime::Frame(30000).seconds((30000, 1001)), 1001.0);
+        assert_eq!(SplitTime::Seconds(2.5).seconds((30, 1)), 2.5);
+    }
+
+    #[test]
+    fn parse_colors() {
+        let parse = |s: &str| s.parse::<Color>().unwrap();
+        assert_eq!(parse("#ff8000"), Color([255, 128, 0]));
+        assert_eq!(parse("00FF7f"), Color([0, 255, 127]));
+        assert_eq!(parse("Gold"), Color([255, 215, 0]));
+        assert_eq!(parse("grey"), parse("gray"));
+        assert!("#fff".parse::<Color>().is_err());
+        assert!("#gg0000".parse::<Color>().is_err());
+        assert!("#ff00ä".parse::<Color>().is_err());
+    }
+
+    #[test]
+    fn ordinals() {
+        let ordinals: Vec<_> = [1, 2, 3, 4, 11, 12, 13, 21, 22, 23, 101, 111, 112]
+            .iter()
+            .map(|n| ordinal(*n))
+            .collect();
+        assert_eq!(ordinals, vec![
+            "1st", "2nd", "3rd", "4th", "11th", "12th", "13th", "21st", "22nd", "23rd", "101st", "111th", "112th"
+        ]);
+    }
+
+    #[test]
+    fn format_times() {
+        assert_eq!(format_time_precision(0.0, 3), "00.000");
+        assert_eq!(format_time_precision(59.9999, 3), "59.999");
+        assert_eq!(format_time_precision(59.9999, 0), "59");
+        assert_eq!(format_time_precision(60.0, 2), "01:00.00");
+        assert_eq!(format_time_precision(3599.99, 1), "59:59.9");
+        assert_eq!(format_time_precision(3600.0, 3), "01:00:00.000");
+        assert_eq!(format_time_precision(-1.5, 1), "-01.5");
+        // floating point errors just below a boundary are not truncated
+        assert_eq!(format_time_precision(0.7 * 3.0, 3), "02.100");
+        assert_eq!(format_time_precision(4.35, 2), "04.35");
+        assert_eq!(format_time(83.4567), "01:23.456");
+    }
+
+    /// Three tiles with two segments, the second one starting after a
+    /// pause. Segment times are 30/20/30 and 50/70/50 frames.
+    fn render_info() -> RenderInfo {
+        let tile = |input, splits: &[(u32, u32)]| RenderTileInfo {
+            input,
+            offset: 0,
+            length: 150,
+            x: 0,
+            y: 0,
+            width: 320,
+            height: 240,
+            fit: FitMode::Fit,
+            splits: splits.to_vec()
+        };
+        RenderInfo {
+            start: 10,
+            length: 150,
+            tiles: vec![
+                tile(0, &[(10, 40), (50, 100)]),
+                tile(1, &[(10, 30), (50, 120)]),
+                tile(2, &[(10, 40), (50, 100)])
+            ],
+            pauses: vec![50],
+            segments: vec![None, None],
+            banner: None,
+            panel: None,
+            results: None
+        }
+    }
+
+    #[test]
+    fn total_diffs() {
+        let info = render_info();
+        assert_eq!(info.total_diff(0, 0, None), 10);
+        assert_eq!(info.total_diff(1, 0, None), 0);
+        assert_eq!(info.total_diff(1, 1, None), 10);
+        assert_eq!(info.total_diff(2, 1, None), 0);
+        assert_eq!(info.total_diff(1, 0, Some(0)), -10);
+        assert_eq!(info.total_diff(0, 1, Some(1)), -10);
+        assert_eq!(info.total_diff(1, 1, Some(1)), 0);
+        assert_eq!(info.total_diff(2, 1, Some(0)), 0);
+    }
+
+    #[test]
+    fn places() {
+        let info = render_info();
+        let segment: Vec<_> = (0..3).map(|i| info.segment_place(i, 0)).collect();
+        assert_eq!(segment, [2, 1, 2]);
+        let segment: Vec<_> = (0..3).map(|i| info.segment_place(i, 1)).collect();
+        assert_eq!(segment, [1, 3, 1]);
+        let total: Vec<_> = (0..3).map(|i| info.total_place(i, 0)).collect();
+        assert_eq!(total, [2, 1, 2]);
+        // Tile 1 wins the first segment but not the total
+        let total: Vec<_> = (0..3).map(|i| info.total_place(i, 1)).collect();
+        assert_eq!(total, [1, 3, 1]);
+    }
+
+    #[test]
+    fn leader_ends() {
+        let info = render_info();
+        let [first, second, third] = [&info.tiles[0], &info.tiles[1], &info.tiles[2]];
+        assert_eq!(info.leader_end(first, 5), None);
+        assert_eq!(info.leader_end(second, 29), None);
+        assert_eq!(info.leader_end(second, 30), Some(30));
+        assert_eq!(info.leader_end(first, 45), None);
+        assert_eq!(info.leader_end(second, 60), None);
+        assert_eq!(info.leader_end(first, 99), None);
+        assert_eq!(info.leader_end(first, 100), Some(100));
+        assert_eq!(info.leader_end(third, 110), Some(100));
+        assert_eq!(info.leader_end(second, 130), None);
+    }
 }
@@ -0,0 +1,147 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use crate::splitscreen::RunSplit;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TimingMethod {
+    RealTime,
+    GameTime
+}
+
+#[derive(Debug, Deserialize)]
+struct Run {
+    #[serde(rename = "_schemaVersion")]
+    schema_version: Option<String>,
+    #[serde(default)]
+    segments: Vec<Segment>
+}
+
+#[derive(Debug, Deserialize)]
+struct Segment {
+    name: Option<String>,
+    #[serde(rename = "endedAt")]
+    ended_at: Option<Duration>
+}
+
+#[derive(Debug, Deserialize)]
+struct Duration {
+    #[serde(rename = "realtimeMS")]
+    realtime_ms: Option<f64>,
+    #[serde(rename = "gametimeMS")]
+    gametime_ms: Option<f64>
+}
+
+/// Reads the personal best of a file in the splits.io exchange format.
+pub fn read_splits(path: &Path, timing: TimingMethod) -> Result<Vec<RunSplit>, Box<dyn Error>> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("cannot open {}: {}", path.display(), e))?;
+    let run: Run = serde_json::from_str(&text)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    if run.schema_version.is_none() {
+        eprintln!("warning: {}: missing schema version, not a splits.io file?", path.display());
+    }
+
+    run_splits(run, timing)
+        .map_err(|e| format!("{}: {}", path.display(), e).into())
+}
+
+/// Returns the end of each segment of `run` in seconds.
+fn run_splits(run: Run, timing: TimingMethod) -> Result<Vec<RunSplit>, Box<dyn Error>> {
+    let mut res = Vec::new();
+    for segment in run.segments {
+        let name = segment.name
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+
+        let time = segment.ended_at
+            .and_then(|ended_at| match timing {
+                TimingMethod::RealTime => ended_at.realtime_ms,
+                TimingMethod::GameTime => ended_at.gametime_ms
+            })
+            .filter(|ms| *ms > 0.0)
+            .ok_or_else(|| format!("no {} time for segment `{}`",
+                timing, name.as_deref().unwrap_or("<unnamed>")))?;
+
+        res.push(RunSplit { name, time: time / 1000.0 });
+    }
+
+    if res.is_empty() {
+        Err("no segments")?;
+    }
+
+    Ok(res)
+}
+
+impl FromStr for TimingMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<TimingMethod, String> {
+        match s {
+            "real" => Ok(TimingMethod::RealTime),
+            "game" => Ok(TimingMethod::GameTime),
+            _ => Err(format!("invalid timing method: {}", s))
+        }
+    }
+}
+
+impl fmt::Display for TimingMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimingMethod::RealTime =>
+                write!(f, "real"),
+            TimingMethod::GameTime =>
+                write!(f, "game")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUN: &str = r#"{
+        "_schemaVersion": "v1.0.1",
+        "segments": [
+            { "name": "Forest", "endedAt": { "realtimeMS": 83456, "gametimeMS": 80000 } },
+            { "name": "", "endedAt": { "realtimeMS": 170000 } }
+        ]
+    }"#;
+
+    fn parse(text: &str, timing: TimingMethod) -> Result<Vec<RunSplit>, Box<dyn Error>> {
+        run_splits(serde_json::from_str(text)?, timing)
+    }
+
+    #[test]
+    fn real_time() {
+        let splits = parse(RUN, TimingMethod::RealTime).unwrap();
+        let times: Vec<_> = splits.iter()
+            .map(|split| (split.name.as_deref(), split.time))
+            .collect();
+        assert_eq!(times, vec![(Some("Forest"), 83.456), (None, 170.0)]);
+    }
+
+    #[test]
+    fn missing_game_time() {
+        let err = parse(RUN, TimingMethod::GameTime).unwrap_err();
+        assert_eq!(err.to_string(), "no game time for segment `<unnamed>`");
+    }
+
+    #[test]
+    fn no_segments() {
+        let err = parse(r#"{ "_schemaVersion": "v1.0.1" }"#, TimingMethod::RealTime).unwrap_err();
+        assert_eq!(err.to_string(), "no segments");
+    }
+
+    #[test]
+    fn parse_timing_method() {
+        assert_eq!("game".parse::<TimingMethod>().unwrap(), TimingMethod::GameTime);
+        assert!("ingame".parse::<TimingMethod>().is_err());
+    }
+}